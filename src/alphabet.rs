@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+pub type Symbol = usize;
+pub type Word = Vec<Symbol>;
+
+// Алфавит, сопоставляющий лексемам (`a`, `if`, `(`, `id`, ...) целочисленные символы
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Alphabet {
+    tokens: Vec<String>,
+    token_to_symbol: HashMap<String, Symbol>,
+    separator: String,
+}
+
+impl Alphabet {
+    pub fn new(separator: &str) -> Self {
+        Self {
+            tokens: Vec::new(),
+            token_to_symbol: HashMap::new(),
+            separator: separator.to_owned(),
+        }
+    }
+
+    // Каждая буква строки -- отдельный символ, слова записываются слитно
+    pub fn from_chars(letters: &str) -> Self {
+        let mut alphabet = Self::new("");
+
+        for letter in letters.chars() {
+            alphabet.intern(&letter.to_string());
+        }
+
+        alphabet
+    }
+
+    // Произвольные лексемы, в записи слова разделяются пробелом
+    pub fn from_tokens<T: AsRef<str>>(tokens: &[T]) -> Self {
+        let mut alphabet = Self::new(" ");
+
+        for token in tokens {
+            alphabet.intern(token.as_ref());
+        }
+
+        alphabet
+    }
+
+    // Слитная запись, если все лексемы односимвольные, иначе через пробел
+    pub fn with_inferred_separator<T: AsRef<str>>(tokens: &[T]) -> Self {
+        let is_single_char = tokens
+            .iter()
+            .all(|token| token.as_ref().chars().count() == 1);
+
+        let mut alphabet = if is_single_char {
+            Self::new("")
+        } else {
+            Self::new(" ")
+        };
+
+        for token in tokens {
+            alphabet.intern(token.as_ref());
+        }
+
+        alphabet
+    }
+
+    pub fn intern(&mut self, token: &str) -> Symbol {
+        if let Some(symbol) = self.token_to_symbol.get(token) {
+            return *symbol;
+        }
+
+        let symbol = self.tokens.len();
+        self.tokens.push(token.to_owned());
+        self.token_to_symbol.insert(token.to_owned(), symbol);
+
+        symbol
+    }
+
+    pub fn get_symbol(&self, token: &str) -> Option<Symbol> {
+        self.token_to_symbol.get(token).copied()
+    }
+
+    pub fn get_token(&self, symbol: Symbol) -> &str {
+        &self.tokens[symbol]
+    }
+
+    pub fn symbols(&self) -> std::ops::Range<Symbol> {
+        0..self.tokens.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    pub fn get_separator(&self) -> &str {
+        &self.separator
+    }
+
+    pub fn format_word(&self, word: &[Symbol]) -> String {
+        let tokens: Vec<&str> = word.iter().map(|symbol| self.get_token(*symbol)).collect();
+        tokens.join(&self.separator)
+    }
+
+    // Запись слова без разделителей, например для разбора грамматикой
+    pub fn concat_word(&self, word: &[Symbol]) -> String {
+        word.iter().map(|symbol| self.get_token(*symbol)).collect()
+    }
+
+    pub fn parse_word(&self, text: &str) -> Option<Word> {
+        if !self.separator.is_empty() {
+            return text
+                .split(self.separator.as_str())
+                .map(str::trim)
+                .filter(|token| !token.is_empty())
+                .map(|token| self.get_symbol(token))
+                .collect();
+        }
+
+        // Без разделителя слово разбивается жадно по самой длинной лексеме
+        let mut word = Word::new();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let (symbol, token) = self
+                .tokens
+                .iter()
+                .enumerate()
+                .filter(|(_, token)| !token.is_empty() && rest.starts_with(token.as_str()))
+                .max_by_key(|(_, token)| token.len())?;
+
+            word.push(symbol);
+            rest = &rest[token.len()..];
+        }
+
+        Some(word)
+    }
+}
@@ -1,81 +1,263 @@
 #![allow(dead_code)]
 
+pub mod conformance;
+pub mod dot;
+pub mod format;
 pub mod reachability;
+pub mod rfsa;
 pub mod str_generator;
 
 use std::any::Any;
 use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 
-use crate::config::EPSILON;
+use crate::alphabet::{Alphabet, Symbol, Word};
 
 pub const START: usize = 0;
 
 pub trait Automata {
     fn as_any(&self) -> &dyn Any;
 
-    fn check_membership(&self, word: &str) -> bool;
+    fn check_membership(&self, word: &[Symbol]) -> bool;
 
     fn determinize(&self) -> Box<dyn Automata>;
 
-    fn get_complement(&self) -> Box<dyn Automata>;
+    fn get_complement(&self, alphabet: &Alphabet) -> Box<dyn Automata>;
 
     fn intersect(&self, other: &dyn Automata) -> Box<dyn Automata>;
 
-    fn generate_word(&self) -> String;
+    fn minimize(&self) -> Box<dyn Automata>;
+
+    fn equivalent(&self, other: &dyn Automata) -> Option<Word>;
+
+    fn get_size(&self) -> usize;
+
+    fn generate_word(&self) -> Word;
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StateTransitions {
+    pub epsilon: BTreeSet<usize>,
+    pub labelled: HashMap<Symbol, BTreeSet<usize>>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct AutomataImpl {
     pub size: usize,
-    pub transitions: Vec<Vec<HashSet<String>>>,
+    pub transitions: Vec<StateTransitions>,
     pub start_states: Vec<bool>,
     pub finite_states: Vec<bool>,
 }
 
-#[derive(Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
 struct IntersectionState {
     first_state: usize,
-    label: String,
     second_state: usize,
 }
 
-impl IntersectionState {
-    fn get_start() -> Self {
-        Self {
-            first_state: START,
-            label: DUMMY_LABEL.to_string(),
-            second_state: START,
-        }
-    }
-}
-
 struct IntersectionDetails {
     state: usize,
     is_finite: bool,
-    incoming_states: Vec<IntersectionState>,
+    outcoming: Vec<(Option<Symbol>, IntersectionState)>,
 }
 
-const DUMMY_LABEL: &str = "";
-const DUMMY_STATE: usize = 0;
-
 impl Automata for AutomataImpl {
     fn as_any(&self) -> &dyn Any {
         self
     }
 
-    fn check_membership(&self, word: &str) -> bool {
-        todo!()
+    fn check_membership(&self, word: &[Symbol]) -> bool {
+        let mut subset = self.get_epsilon_closure(&self.get_start_subset());
+
+        for letter in word {
+            let mut next_subset = BTreeSet::<usize>::new();
+            for state in &subset {
+                if let Some(next_states) = self.transitions[*state].labelled.get(letter) {
+                    next_subset.extend(next_states);
+                }
+            }
+
+            if next_subset.is_empty() {
+                return false;
+            }
+            subset = self.get_epsilon_closure(&next_subset);
+        }
+
+        subset.iter().any(|state| self.finite_states[*state])
     }
 
     fn determinize(&self) -> Box<dyn Automata> {
+        Box::new(self.determinize_impl())
+    }
+
+    fn get_complement(&self, alphabet: &Alphabet) -> Box<dyn Automata> {
+        Box::new(self.get_complement_impl(alphabet))
+    }
+
+    fn intersect(&self, other: &dyn Automata) -> Box<dyn Automata> {
+        let other = other.as_any().downcast_ref::<AutomataImpl>().unwrap();
+
+        let mut state_to_details = HashMap::<IntersectionState, IntersectionDetails>::new();
+        let mut start_states = Vec::<IntersectionState>::new();
+        let mut states_to_visit = VecDeque::<IntersectionState>::new();
+
+        for first_state in self.get_start_subset() {
+            for second_state in other.get_start_subset() {
+                let state = IntersectionState {
+                    first_state,
+                    second_state,
+                };
+                let details = self.get_intersection_details(other, state, state_to_details.len());
+                state_to_details.insert(state, details);
+                start_states.push(state);
+                states_to_visit.push_back(state);
+            }
+        }
+
+        // Обходим только достижимые пары состояний
+        while let Some(state) = states_to_visit.pop_front() {
+            let outcoming = self.get_intersection_outcoming(other, state);
+
+            for (_, next_state) in &outcoming {
+                if !state_to_details.contains_key(next_state) {
+                    let details =
+                        self.get_intersection_details(other, *next_state, state_to_details.len());
+                    state_to_details.insert(*next_state, details);
+                    states_to_visit.push_back(*next_state);
+                }
+            }
+
+            state_to_details.get_mut(&state).unwrap().outcoming = outcoming;
+        }
+
+        // Нет ни одной пары стартовых состояний
+        if state_to_details.is_empty() {
+            return Box::new(Self::new(1));
+        }
+
+        let mut automata = Self::new(state_to_details.len());
+
+        for state in &start_states {
+            let index = state_to_details.get(state).unwrap().state;
+            automata.start_states[index] = true;
+        }
+
+        for details in state_to_details.values() {
+            automata.finite_states[details.state] = details.is_finite;
+
+            for (label, next_state) in &details.outcoming {
+                let next_index = state_to_details.get(next_state).unwrap().state;
+                match label {
+                    Some(label) => automata.add_transition(details.state, *label, next_index),
+                    None => automata.add_epsilon_transition(details.state, next_index),
+                }
+            }
+        }
+
+        Box::new(automata)
+    }
+
+    fn minimize(&self) -> Box<dyn Automata> {
+        Box::new(self.minimize_impl())
+    }
+
+    fn equivalent(&self, other: &dyn Automata) -> Option<Word> {
+        let other = other.as_any().downcast_ref::<AutomataImpl>().unwrap();
+        self.find_shortest_difference(other)
+    }
+
+    fn get_size(&self) -> usize {
+        self.size
+    }
+
+    fn generate_word(&self) -> Word {
+        todo!();
+    }
+}
+
+impl AutomataImpl {
+    pub fn new(size: usize) -> Self {
+        let mut start_states = vec![false; size];
+        start_states[START] = true;
+
+        let transitions = vec![StateTransitions::default(); size];
+
+        let finite_states = vec![false; size];
+
+        Self {
+            start_states,
+            transitions,
+            finite_states,
+            size,
+        }
+    }
+
+    // Пустая метка в матричном представлении соответствует эпсилон-переходу
+    pub fn from_matrix(matrix: &[Vec<HashSet<String>>], alphabet: &mut Alphabet) -> Self {
+        let mut automata = Self::new(matrix.len());
+
+        for (state, row) in matrix.iter().enumerate() {
+            for (next_state, labels) in row.iter().enumerate() {
+                for label in labels {
+                    if label.is_empty() {
+                        automata.add_epsilon_transition(state, next_state);
+                    } else {
+                        automata.add_transition(state, alphabet.intern(label), next_state);
+                    }
+                }
+            }
+        }
+
+        automata
+    }
+
+    pub fn to_matrix(&self, alphabet: &Alphabet) -> Vec<Vec<HashSet<String>>> {
+        let mut matrix = vec![vec![HashSet::<String>::new(); self.size]; self.size];
+
+        for (state, transitions) in self.transitions.iter().enumerate() {
+            for next_state in &transitions.epsilon {
+                matrix[state][*next_state].insert(String::new());
+            }
+            for (label, next_states) in &transitions.labelled {
+                for next_state in next_states {
+                    matrix[state][*next_state].insert(alphabet.get_token(*label).to_owned());
+                }
+            }
+        }
+
+        matrix
+    }
+
+    pub fn add_transition(&mut self, from: usize, label: Symbol, to: usize) {
+        self.transitions[from]
+            .labelled
+            .entry(label)
+            .or_default()
+            .insert(to);
+    }
+
+    pub fn add_epsilon_transition(&mut self, from: usize, to: usize) {
+        self.transitions[from].epsilon.insert(to);
+    }
+
+    pub fn get_labels(&self) -> BTreeSet<Symbol> {
+        let mut labels = BTreeSet::<Symbol>::new();
+
+        for transitions in &self.transitions {
+            labels.extend(transitions.labelled.keys());
+        }
+
+        labels
+    }
+
+    fn determinize_impl(&self) -> Self {
         #[derive(Eq, Hash, PartialEq)]
         struct Transition {
             from: usize,
-            label: String,
+            label: Symbol,
             to: usize,
         }
 
-        let start_subset = BTreeSet::from([START]);
+        let start_subset = self.get_start_subset();
         let mut subset_to_state = HashMap::from([(start_subset.to_owned(), START)]);
         let mut state_to_subset = HashMap::from([(START, start_subset)]);
         let mut state_counter = START + 1;
@@ -85,7 +267,7 @@ impl Automata for AutomataImpl {
         let mut transitions = HashSet::<Transition>::new();
 
         while let Some(state) = states_to_visit.pop_front() {
-            let mut label_to_subset = HashMap::<String, BTreeSet<usize>>::new();
+            let mut label_to_subset = HashMap::<Symbol, BTreeSet<usize>>::new();
             let closure = self.get_epsilon_closure(state_to_subset.get(&state).unwrap());
 
             for closure_state in closure {
@@ -93,17 +275,11 @@ impl Automata for AutomataImpl {
                     finite_states.insert(state);
                 }
 
-                for (next_state, labels) in self.transitions[closure_state].iter().enumerate() {
-                    for label in labels {
-                        if label.eq(&EPSILON) {
-                            continue;
-                        }
-
-                        if let Some(next_subset) = label_to_subset.get_mut(label) {
-                            next_subset.insert(next_state);
-                        } else {
-                            label_to_subset.insert(label.to_owned(), BTreeSet::from([next_state]));
-                        }
+                for (label, next_states) in &self.transitions[closure_state].labelled {
+                    if let Some(next_subset) = label_to_subset.get_mut(label) {
+                        next_subset.extend(next_states);
+                    } else {
+                        label_to_subset.insert(*label, next_states.to_owned());
                     }
                 }
             }
@@ -124,7 +300,7 @@ impl Automata for AutomataImpl {
 
                 let transition = Transition {
                     from: state,
-                    label: label.to_owned(),
+                    label: *label,
                     to: next_state,
                 };
                 transitions.insert(transition);
@@ -134,59 +310,358 @@ impl Automata for AutomataImpl {
         let mut automata = Self::new(state_counter);
 
         for transition in transitions {
-            automata.transitions[transition.from][transition.to]
-                .insert(transition.label.to_owned());
+            automata.add_transition(transition.from, transition.label, transition.to);
         }
 
         for state in finite_states {
             automata.finite_states[state] = true;
         }
 
-        Box::new(automata)
+        automata
+    }
+
+    fn get_complement_impl(&self, alphabet: &Alphabet) -> Self {
+        let dfa = self.determinize_impl();
+
+        // Последнее состояние -- сток, дополняющий автомат над алфавитом
+        let sink = dfa.size;
+        let mut automata = Self::new(dfa.size + 1);
+
+        for (state, transitions) in dfa.transitions.iter().enumerate() {
+            for (label, next_states) in &transitions.labelled {
+                // Переходы по буквам вне алфавита не принадлежат Σ*
+                if *label >= alphabet.len() {
+                    continue;
+                }
+
+                for next_state in next_states {
+                    automata.add_transition(state, *label, *next_state);
+                }
+            }
+            automata.finite_states[state] = !dfa.finite_states[state];
+        }
+        automata.finite_states[sink] = true;
+
+        for state in 0..automata.size {
+            for label in alphabet.symbols() {
+                if !automata.transitions[state].labelled.contains_key(&label) {
+                    automata.add_transition(state, label, sink);
+                }
+            }
+        }
+
+        automata
     }
 
-    fn get_complement(&self) -> Box<dyn Automata> {
-        todo!() // доработать существующую версию для новой версии AutomataImpl
+    fn minimize_impl(&self) -> Self {
+        let dfa = self.determinize_impl();
+
+        // Состояния детерминизации достижимы, остаётся удалить тупиковые
+        let live_states = dfa.get_live_states();
+        if !live_states.contains(&START) {
+            return Self::new(1);
+        }
+
+        let labels = dfa.get_labels();
+
+        // Сток с индексом dfa.size доопределяет частичную функцию переходов
+        let sink = dfa.size;
+        let mut incoming = HashMap::<(usize, Symbol), Vec<usize>>::new();
+        for state in 0..=sink {
+            for label in &labels {
+                let next_state = dfa.get_next_state(state, *label).unwrap_or(sink);
+                let next_state = if live_states.contains(&next_state) {
+                    next_state
+                } else {
+                    sink
+                };
+                incoming
+                    .entry((next_state, *label))
+                    .or_default()
+                    .push(state);
+            }
+        }
+
+        let partition = Self::refine_partition(&dfa, &live_states, &labels, &incoming);
+
+        let mut state_to_class = vec![0; sink + 1];
+        for (class, states) in partition.iter().enumerate() {
+            for state in states {
+                state_to_class[*state] = class;
+            }
+        }
+
+        // Нумеруем классы в порядке обхода в ширину, сток отбрасываем
+        let sink_class = state_to_class[sink];
+        let mut class_to_state = HashMap::from([(state_to_class[START], START)]);
+        let mut classes_to_visit = VecDeque::from([state_to_class[START]]);
+        let mut transitions = Vec::<(usize, Symbol, usize)>::new();
+
+        while let Some(class) = classes_to_visit.pop_front() {
+            let representative = *partition[class].iter().next().unwrap();
+            for label in &labels {
+                let next_class = match dfa.get_next_state(representative, *label) {
+                    Some(next_state) => state_to_class[next_state],
+                    None => sink_class,
+                };
+                if next_class == sink_class {
+                    continue;
+                }
+
+                if !class_to_state.contains_key(&next_class) {
+                    class_to_state.insert(next_class, class_to_state.len());
+                    classes_to_visit.push_back(next_class);
+                }
+
+                transitions.push((class, *label, next_class));
+            }
+        }
+
+        let mut automata = Self::new(class_to_state.len());
+
+        for (class, state) in &class_to_state {
+            let representative = *partition[*class].iter().next().unwrap();
+            automata.finite_states[*state] = dfa.finite_states[representative];
+        }
+
+        for (class, label, next_class) in transitions {
+            let state = *class_to_state.get(&class).unwrap();
+            let next_state = *class_to_state.get(&next_class).unwrap();
+            automata.add_transition(state, label, next_state);
+        }
+
+        automata
     }
 
-    fn intersect(&self, other: &dyn Automata) -> Box<dyn Automata> {
-        let other = other.as_any().downcast_ref::<AutomataImpl>().unwrap();
+    // Алгоритм Хопкрофта над доопределённым стоком автоматом
+    fn refine_partition(
+        dfa: &Self,
+        live_states: &HashSet<usize>,
+        labels: &BTreeSet<Symbol>,
+        incoming: &HashMap<(usize, Symbol), Vec<usize>>,
+    ) -> Vec<BTreeSet<usize>> {
+        let sink = dfa.size;
+
+        let mut finite = BTreeSet::<usize>::new();
+        let mut non_finite = BTreeSet::<usize>::from([sink]);
+        for state in 0..dfa.size {
+            if live_states.contains(&state) && dfa.finite_states[state] {
+                finite.insert(state);
+            } else {
+                non_finite.insert(state);
+            }
+        }
 
-        let mut state_to_details = HashMap::<IntersectionState, IntersectionDetails>::new();
-        state_to_details.insert(
-            IntersectionState::get_start(),
-            IntersectionDetails {
-                state: START,
-                is_finite: self.finite_states[START] && other.finite_states[START],
-                incoming_states: Vec::<IntersectionState>::new(),
-            },
-        );
+        let mut partition = vec![finite, non_finite];
+        partition.retain(|class| !class.is_empty());
+
+        let mut state_to_class = vec![0; sink + 1];
+        for (class, states) in partition.iter().enumerate() {
+            for state in states {
+                state_to_class[*state] = class;
+            }
+        }
+
+        let mut waiting = (0..partition.len()).collect::<Vec<_>>();
 
-        todo!()
+        while let Some(splitter) = waiting.pop() {
+            let splitter_states = partition[splitter].clone();
+
+            for label in labels {
+                let mut predecessors = BTreeSet::<usize>::new();
+                for state in &splitter_states {
+                    if let Some(states) = incoming.get(&(*state, *label)) {
+                        predecessors.extend(states);
+                    }
+                }
+
+                let mut touched_classes = BTreeSet::<usize>::new();
+                for state in &predecessors {
+                    touched_classes.insert(state_to_class[*state]);
+                }
+
+                for class in touched_classes {
+                    let (inside, outside): (BTreeSet<usize>, BTreeSet<usize>) = partition[class]
+                        .iter()
+                        .partition(|state| predecessors.contains(state));
+                    if outside.is_empty() {
+                        continue;
+                    }
+
+                    let new_class = partition.len();
+                    let (kept, moved) = if inside.len() <= outside.len() {
+                        (outside, inside)
+                    } else {
+                        (inside, outside)
+                    };
+                    for state in &moved {
+                        state_to_class[*state] = new_class;
+                    }
+                    partition[class] = kept;
+                    partition.push(moved);
+
+                    // Если класс уже ждал обработки, ждать будут обе его части,
+                    // иначе достаточно меньшей из них
+                    waiting.push(new_class);
+                }
+            }
+        }
+
+        partition
     }
 
-    fn generate_word(&self) -> String {
-        todo!();
+    fn find_shortest_difference(&self, other: &Self) -> Option<Word> {
+        let first = self.determinize_impl();
+        let second = other.determinize_impl();
+
+        let mut labels = first.get_labels();
+        labels.append(&mut second.get_labels());
+
+        // None соответствует стоку недоопределённого автомата
+        let start = (Some(START), Some(START));
+        let mut state_to_word = HashMap::from([(start, Word::new())]);
+        let mut states_to_visit = VecDeque::from([start]);
+
+        while let Some(state) = states_to_visit.pop_front() {
+            let word = state_to_word.get(&state).unwrap().to_owned();
+
+            let (first_state, second_state) = state;
+            let is_first_finite = first_state.is_some_and(|state| first.finite_states[state]);
+            let is_second_finite = second_state.is_some_and(|state| second.finite_states[state]);
+            if is_first_finite != is_second_finite {
+                return Some(word);
+            }
+
+            for label in &labels {
+                let next_state = (
+                    first_state.and_then(|state| first.get_next_state(state, *label)),
+                    second_state.and_then(|state| second.get_next_state(state, *label)),
+                );
+                if next_state == (None, None) || state_to_word.contains_key(&next_state) {
+                    continue;
+                }
+
+                let mut next_word = word.clone();
+                next_word.push(*label);
+
+                state_to_word.insert(next_state, next_word);
+                states_to_visit.push_back(next_state);
+            }
+        }
+
+        None
     }
-}
 
-impl AutomataImpl {
-    pub fn new(size: usize) -> Self {
-        let mut start_states = vec![false; size];
-        start_states[START] = true;
+    fn get_live_states(&self) -> HashSet<usize> {
+        let mut incoming = vec![Vec::<usize>::new(); self.size];
+        for (state, transitions) in self.transitions.iter().enumerate() {
+            for next_state in transitions.get_next_states() {
+                incoming[next_state].push(state);
+            }
+        }
 
-        let transitions = vec![vec![HashSet::<String>::new(); size]; size];
+        let mut live_states = HashSet::<usize>::new();
+        let mut states_to_visit = VecDeque::<usize>::new();
+        for state in 0..self.size {
+            if self.finite_states[state] {
+                live_states.insert(state);
+                states_to_visit.push_back(state);
+            }
+        }
 
-        let finite_states = vec![false; size];
+        while let Some(state) = states_to_visit.pop_front() {
+            for previous_state in &incoming[state] {
+                if live_states.insert(*previous_state) {
+                    states_to_visit.push_back(*previous_state);
+                }
+            }
+        }
 
-        Self {
-            start_states,
-            transitions,
-            finite_states,
-            size,
+        live_states
+    }
+
+    fn get_next_state(&self, state: usize, label: Symbol) -> Option<usize> {
+        if state >= self.size {
+            return None;
+        }
+
+        self.transitions[state]
+            .labelled
+            .get(&label)
+            .and_then(|next_states| next_states.first().copied())
+    }
+
+    fn get_intersection_details(
+        &self,
+        other: &Self,
+        state: IntersectionState,
+        index: usize,
+    ) -> IntersectionDetails {
+        IntersectionDetails {
+            state: index,
+            is_finite: self.finite_states[state.first_state]
+                && other.finite_states[state.second_state],
+            outcoming: Vec::new(),
         }
     }
 
+    fn get_intersection_outcoming(
+        &self,
+        other: &Self,
+        state: IntersectionState,
+    ) -> Vec<(Option<Symbol>, IntersectionState)> {
+        let mut outcoming = Vec::<(Option<Symbol>, IntersectionState)>::new();
+
+        let first_transitions = &self.transitions[state.first_state];
+        let second_transitions = &other.transitions[state.second_state];
+
+        // Эпсилон-переходы каждого из автоматов выполняются независимо
+        for first_state in &first_transitions.epsilon {
+            let next_state = IntersectionState {
+                first_state: *first_state,
+                second_state: state.second_state,
+            };
+            outcoming.push((None, next_state));
+        }
+        for second_state in &second_transitions.epsilon {
+            let next_state = IntersectionState {
+                first_state: state.first_state,
+                second_state: *second_state,
+            };
+            outcoming.push((None, next_state));
+        }
+
+        for (label, first_next_states) in &first_transitions.labelled {
+            let Some(second_next_states) = second_transitions.labelled.get(label) else {
+                continue;
+            };
+
+            for first_state in first_next_states {
+                for second_state in second_next_states {
+                    let next_state = IntersectionState {
+                        first_state: *first_state,
+                        second_state: *second_state,
+                    };
+                    outcoming.push((Some(*label), next_state));
+                }
+            }
+        }
+
+        outcoming
+    }
+
+    fn get_start_subset(&self) -> BTreeSet<usize> {
+        let mut subset = BTreeSet::<usize>::new();
+
+        for (state, is_start) in self.start_states.iter().enumerate() {
+            if *is_start {
+                subset.insert(state);
+            }
+        }
+
+        subset
+    }
+
     fn get_epsilon_closure(&self, subset: &BTreeSet<usize>) -> BTreeSet<usize> {
         let mut closure = BTreeSet::<usize>::new();
 
@@ -204,11 +679,9 @@ impl AutomataImpl {
         while let Some(state) = states_to_visit.pop_front() {
             visited_states.insert(state);
 
-            for (next_state, labels) in self.transitions[state].iter().enumerate() {
-                for label in labels {
-                    if label.eq(&EPSILON) && !visited_states.contains(&next_state) {
-                        states_to_visit.push_back(next_state);
-                    }
+            for next_state in &self.transitions[state].epsilon {
+                if !visited_states.contains(next_state) {
+                    states_to_visit.push_back(*next_state);
                 }
             }
         }
@@ -217,17 +690,33 @@ impl AutomataImpl {
     }
 
     pub fn is_start_state(&self, i: usize) -> bool {
-        return self.start_states[i];
+        self.start_states[i]
     }
 
     pub fn is_finite_state(&self, i: usize) -> bool {
-        return self.finite_states[i];
+        self.finite_states[i]
     }
 
     pub fn is_empty(&self) -> bool {
         self.size == 1
             && self.is_start_state(START)
-            && self.transitions[START][START].is_empty()
+            && self.transitions[START].is_empty()
             && !self.is_finite_state(START)
     }
 }
+
+impl StateTransitions {
+    pub fn is_empty(&self) -> bool {
+        self.epsilon.is_empty() && self.labelled.is_empty()
+    }
+
+    pub fn get_next_states(&self) -> BTreeSet<usize> {
+        let mut next_states = self.epsilon.clone();
+
+        for states in self.labelled.values() {
+            next_states.extend(states);
+        }
+
+        next_states
+    }
+}
@@ -0,0 +1,275 @@
+// Наборы тестов соответствия для минимального ДКА (W-метод и Wp-метод).
+// Если язык распознаётся автоматом, у которого не более чем на extra_states
+// состояний больше, чем у гипотезы, набор содержит контрпример к эквивалентности.
+
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+use crate::alphabet::{Alphabet, Symbol, Word};
+
+use super::{AutomataImpl, START};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConformanceMethod {
+    W,
+    Wp,
+}
+
+// Полная таблица переходов, недостающие переходы ведут в сток
+struct CompleteDfa {
+    transitions: Vec<Vec<usize>>,
+    finite_states: Vec<bool>,
+}
+
+// Остаток языка -- состояние полного минимального ДКА. including[other] -- вложен ли
+// остаток в остаток other, простой остаток не равен объединению строго вложенных в него
+pub(super) struct Residual {
+    pub(super) next_states: Vec<usize>,
+    pub(super) is_final: bool,
+    pub(super) including: Vec<bool>,
+    pub(super) is_prime: bool,
+}
+
+// Слова доступа состояний ДКА, вычисленные один раз для многих запросов
+pub struct AccessWords {
+    dfa: CompleteDfa,
+    access_words: Vec<Option<Word>>,
+}
+
+impl AutomataImpl {
+    // Автомат должен быть минимальным ДКА, например результатом minimize
+    pub fn get_conformance_suite(
+        &self,
+        alphabet: &Alphabet,
+        method: ConformanceMethod,
+        extra_states: usize,
+    ) -> Vec<Word> {
+        let dfa = self.get_complete_dfa(alphabet);
+        let access_words = dfa.get_access_words();
+        let state_identifiers = dfa.get_state_identifiers();
+        let characterizing_set: BTreeSet<&Word> = state_identifiers.iter().flatten().collect();
+
+        // Слова длины не больше extra_states для поиска недостающих состояний
+        let mut middles = vec![Word::new()];
+        let mut layer = vec![Word::new()];
+        for _ in 0..extra_states {
+            layer = layer
+                .iter()
+                .flat_map(|word| {
+                    alphabet
+                        .symbols()
+                        .map(move |letter| [word.as_slice(), &[letter]].concat())
+                })
+                .collect();
+            middles.extend(layer.iter().cloned());
+        }
+
+        let mut suite = BTreeSet::<(usize, Word)>::new();
+        for access_word in access_words.iter().flatten() {
+            for letter in std::iter::once(None).chain(alphabet.symbols().map(Some)) {
+                let prefix = match letter {
+                    Some(letter) => [access_word.as_slice(), &[letter]].concat(),
+                    None => access_word.clone(),
+                };
+
+                for middle in &middles {
+                    let word = [prefix.as_slice(), middle].concat();
+
+                    // Wp: слова покрытия переходов проверяются только идентификатором состояния
+                    let suffixes: BTreeSet<&Word> = match (method, letter) {
+                        (ConformanceMethod::Wp, Some(_)) => {
+                            state_identifiers[dfa.get_state(&word)].iter().collect()
+                        }
+                        _ => characterizing_set.clone(),
+                    };
+
+                    for suffix in suffixes {
+                        let test = [word.as_slice(), suffix].concat();
+                        suite.insert((test.len(), test));
+                    }
+                }
+            }
+        }
+
+        suite.into_iter().map(|(_, word)| word).collect()
+    }
+
+    pub fn get_access_words(&self, alphabet: &Alphabet) -> AccessWords {
+        let dfa = self.get_complete_dfa(alphabet);
+        let access_words = dfa.get_access_words();
+
+        AccessWords { dfa, access_words }
+    }
+
+    // Автомат должен быть минимальным ДКА, остаток языка -- состояние START
+    pub(super) fn get_residuals(&self, alphabet: &Alphabet) -> Vec<Residual> {
+        let dfa = self.get_complete_dfa(alphabet);
+
+        let size = dfa.transitions.len();
+        let including: Vec<Vec<bool>> = (0..size)
+            .map(|first| {
+                (0..size)
+                    .map(|second| dfa.is_covered(first, BTreeSet::from([second])))
+                    .collect()
+            })
+            .collect();
+
+        (0..size)
+            .map(|state| {
+                let smaller_states = (0..size)
+                    .filter(|other| *other != state && including[*other][state])
+                    .collect();
+
+                Residual {
+                    next_states: dfa.transitions[state].clone(),
+                    is_final: dfa.finite_states[state],
+                    including: including[state].clone(),
+                    is_prime: !dfa.is_covered(state, smaller_states),
+                }
+            })
+            .collect()
+    }
+
+    fn get_complete_dfa(&self, alphabet: &Alphabet) -> CompleteDfa {
+        // Минимальный автомат пустого языка уже состоит из одного отвергающего состояния
+        let sink = if self.is_empty() { START } else { self.size };
+
+        let mut transitions = Vec::<Vec<usize>>::with_capacity(self.size + 1);
+        for state in 0..self.size {
+            transitions.push(
+                alphabet
+                    .symbols()
+                    .map(|letter| self.get_next_state(state, letter).unwrap_or(sink))
+                    .collect(),
+            );
+        }
+        let mut finite_states = self.finite_states.clone();
+
+        if sink == self.size && transitions.iter().flatten().any(|state| *state == sink) {
+            transitions.push(vec![sink; alphabet.len()]);
+            finite_states.push(false);
+        }
+
+        CompleteDfa {
+            transitions,
+            finite_states,
+        }
+    }
+}
+
+impl AccessWords {
+    // Кратчайшее слово, ведущее в то же состояние ДКА, что и word
+    pub fn get(&self, word: &[Symbol]) -> &Word {
+        self.access_words[self.dfa.get_state(word)]
+            .as_ref()
+            .unwrap()
+    }
+}
+
+impl CompleteDfa {
+    fn get_state(&self, word: &[usize]) -> usize {
+        word.iter()
+            .fold(START, |state, letter| self.transitions[state][*letter])
+    }
+
+    // Вложен ли язык состояния в объединение языков states; при пустом states --
+    // пуст ли язык состояния
+    fn is_covered(&self, state: usize, states: BTreeSet<usize>) -> bool {
+        let mut visited = HashSet::from([(state, states.clone())]);
+        let mut pairs_to_visit = VecDeque::from([(state, states)]);
+
+        while let Some((state, states)) = pairs_to_visit.pop_front() {
+            if self.finite_states[state] && !states.iter().any(|other| self.finite_states[*other]) {
+                return false;
+            }
+
+            for (letter, next_state) in self.transitions[state].iter().enumerate() {
+                let next_states: BTreeSet<usize> = states
+                    .iter()
+                    .map(|other| self.transitions[*other][letter])
+                    .collect();
+                if visited.insert((*next_state, next_states.clone())) {
+                    pairs_to_visit.push_back((*next_state, next_states));
+                }
+            }
+        }
+
+        true
+    }
+
+    // Кратчайшие слова, ведущие в каждое состояние (покрытие состояний)
+    fn get_access_words(&self) -> Vec<Option<Word>> {
+        let mut access_words = vec![None::<Word>; self.transitions.len()];
+        access_words[START] = Some(Word::new());
+
+        let mut states_to_visit = VecDeque::from([START]);
+        while let Some(state) = states_to_visit.pop_front() {
+            let access_word = access_words[state].clone().unwrap();
+            for (letter, next_state) in self.transitions[state].iter().enumerate() {
+                if access_words[*next_state].is_none() {
+                    access_words[*next_state] = Some([access_word.as_slice(), &[letter]].concat());
+                    states_to_visit.push_back(*next_state);
+                }
+            }
+        }
+
+        access_words
+    }
+
+    // Кратчайшие слова, различающие каждую пару состояний
+    fn get_separating_words(&self) -> Vec<Vec<Option<Word>>> {
+        let size = self.transitions.len();
+        let mut separating_words = vec![vec![None::<Word>; size]; size];
+
+        for (first, row) in separating_words.iter_mut().enumerate() {
+            for (second, separating_word) in row.iter_mut().enumerate() {
+                if self.finite_states[first] != self.finite_states[second] {
+                    *separating_word = Some(Word::new());
+                }
+            }
+        }
+
+        // На каждом шаге различаются пары, переходящие по букве в уже различённые
+        loop {
+            let mut updates = Vec::<(usize, usize, Word)>::new();
+            for first in 0..size {
+                for second in 0..size {
+                    if first == second || separating_words[first][second].is_some() {
+                        continue;
+                    }
+
+                    for (letter, next_first) in self.transitions[first].iter().enumerate() {
+                        let next_second = self.transitions[second][letter];
+                        if let Some(suffix) = &separating_words[*next_first][next_second] {
+                            let word = [&[letter], suffix.as_slice()].concat();
+                            updates.push((first, second, word));
+                            break;
+                        }
+                    }
+                }
+            }
+
+            if updates.is_empty() {
+                break;
+            }
+            for (first, second, word) in updates {
+                separating_words[first][second] = Some(word);
+            }
+        }
+
+        separating_words
+    }
+
+    // Слова, отличающие состояние от всех остальных; у единственного состояния -- ε
+    fn get_state_identifiers(&self) -> Vec<BTreeSet<Word>> {
+        self.get_separating_words()
+            .into_iter()
+            .map(|row| {
+                let mut identifier: BTreeSet<Word> = row.into_iter().flatten().collect();
+                if identifier.is_empty() {
+                    identifier.insert(Word::new());
+                }
+                identifier
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+use crate::alphabet::Alphabet;
+
+use super::AutomataImpl;
+
+impl AutomataImpl {
+    // Описание автомата на языке DOT (Graphviz). Параллельные переходы
+    // объединяются в одно ребро, эпсилон-переходы рисуются пунктиром.
+    pub fn to_dot(
+        &self,
+        alphabet: &Alphabet,
+        state_annotations: Option<&HashMap<usize, String>>,
+    ) -> String {
+        let mut dot = String::new();
+
+        writeln!(dot, "digraph automata {{").unwrap();
+        writeln!(dot, "    rankdir=LR;").unwrap();
+        writeln!(dot, "    node [shape=circle];").unwrap();
+
+        for state in 0..self.size {
+            let mut label = state.to_string();
+            if let Some(annotation) = state_annotations.and_then(|map| map.get(&state)) {
+                label.push_str("\\n");
+                label.push_str(&escape(annotation));
+            }
+
+            let shape = if self.finite_states[state] {
+                "doublecircle"
+            } else {
+                "circle"
+            };
+            writeln!(dot, "    {state} [label=\"{label}\", shape={shape}];").unwrap();
+
+            if self.start_states[state] {
+                writeln!(dot, "    start_{state} [shape=point];").unwrap();
+                writeln!(dot, "    start_{state} -> {state};").unwrap();
+            }
+        }
+
+        for (state, transitions) in self.transitions.iter().enumerate() {
+            for next_state in &transitions.epsilon {
+                writeln!(
+                    dot,
+                    "    {state} -> {next_state} [label=\"ε\", style=dashed];"
+                )
+                .unwrap();
+            }
+
+            let mut state_to_labels = BTreeMap::<usize, BTreeSet<&str>>::new();
+            for (label, next_states) in &transitions.labelled {
+                for next_state in next_states {
+                    state_to_labels
+                        .entry(*next_state)
+                        .or_default()
+                        .insert(alphabet.get_token(*label));
+                }
+            }
+
+            for (next_state, labels) in state_to_labels {
+                let labels: Vec<String> = labels.into_iter().map(escape).collect();
+                let label = labels.join(", ");
+                writeln!(dot, "    {state} -> {next_state} [label=\"{label}\"];").unwrap();
+            }
+        }
+
+        writeln!(dot, "}}").unwrap();
+
+        dot
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
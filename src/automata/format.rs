@@ -0,0 +1,360 @@
+// Сохранение и загрузка автоматов.
+//
+// JSON (схема -- docs/automata.schema.json):
+//
+//     {
+//       "alphabet": ["a", "b"],
+//       "separator": "",
+//       "size": 2,
+//       "start_states": [0],
+//       "finite_states": [1],
+//       "transitions": [
+//         { "from": 0, "label": "a", "to": 1 },
+//         { "from": 1, "label": null, "to": 0 }
+//       ]
+//     }
+//
+// Метка null обозначает эпсилон-переход.
+//
+// Текстовый формат -- по одной директиве или переходу в строке,
+// пустые строки и строки, начинающиеся с '#', пропускаются:
+//
+//     states 2
+//     alphabet a b
+//     separator ""
+//     start 0
+//     final 1
+//     0 a 1
+//     1 ε 0
+//
+// Лексемы алфавита разделяются пробелами, метка ε обозначает эпсилон-переход.
+// Лексема ε и лексемы, начинающиеся с '\', записываются с префиксом '\'.
+// separator -- разделитель лексем в записи слов строкой JSON; без этой директивы
+// слова записываются слитно, если все лексемы односимвольные, иначе через пробел.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::alphabet::{Alphabet, Symbol};
+
+use super::AutomataImpl;
+
+const TEXT_EPSILON: &str = "ε";
+const TEXT_ESCAPE: char = '\\';
+
+#[derive(Debug)]
+pub enum FormatError {
+    Json(serde_json::Error),
+    Syntax { line: usize, message: String },
+    UnknownLabel(String),
+    InvalidState(usize),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(error) => write!(f, "invalid JSON: {error}"),
+            Self::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            Self::UnknownLabel(label) => write!(f, "label '{label}' is not in the alphabet"),
+            Self::InvalidState(state) => write!(f, "state {state} is out of range"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct AutomataJson {
+    alphabet: Vec<String>,
+    separator: String,
+    size: usize,
+    start_states: Vec<usize>,
+    finite_states: Vec<usize>,
+    transitions: Vec<TransitionJson>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct TransitionJson {
+    from: usize,
+    label: Option<String>,
+    to: usize,
+}
+
+impl AutomataImpl {
+    pub fn to_json(&self, alphabet: &Alphabet) -> String {
+        let mut transitions = Vec::<TransitionJson>::new();
+        for (from, label, to) in self.get_sorted_transitions() {
+            transitions.push(TransitionJson {
+                from,
+                label: label.map(|label| alphabet.get_token(label).to_owned()),
+                to,
+            });
+        }
+
+        let automata = AutomataJson {
+            alphabet: alphabet
+                .symbols()
+                .map(|symbol| alphabet.get_token(symbol).to_owned())
+                .collect(),
+            separator: alphabet.get_separator().to_owned(),
+            size: self.size,
+            start_states: Self::get_marked_states(&self.start_states),
+            finite_states: Self::get_marked_states(&self.finite_states),
+            transitions,
+        };
+
+        serde_json::to_string_pretty(&automata).unwrap()
+    }
+
+    pub fn from_json(text: &str) -> Result<(Self, Alphabet), FormatError> {
+        let automata: AutomataJson = serde_json::from_str(text).map_err(FormatError::Json)?;
+
+        let mut alphabet = Alphabet::new(&automata.separator);
+        for token in &automata.alphabet {
+            alphabet.intern(token);
+        }
+
+        let mut result = Self::with_size(automata.size)?;
+        result.start_states[super::START] = false;
+        result.set_marked_states(&automata.start_states, true)?;
+        result.set_marked_states(&automata.finite_states, false)?;
+
+        for transition in automata.transitions {
+            let label = match transition.label {
+                Some(token) => Some(
+                    alphabet
+                        .get_symbol(&token)
+                        .ok_or(FormatError::UnknownLabel(token))?,
+                ),
+                None => None,
+            };
+            result.add_checked_transition(transition.from, label, transition.to)?;
+        }
+
+        Ok((result, alphabet))
+    }
+
+    pub fn to_text(&self, alphabet: &Alphabet) -> String {
+        let mut lines = Vec::<String>::new();
+
+        lines.push(format!("states {}", self.size));
+
+        let tokens: Vec<String> = alphabet
+            .symbols()
+            .map(|symbol| Self::escape_token(alphabet.get_token(symbol)))
+            .collect();
+        lines.push(
+            format!("alphabet {}", tokens.join(" "))
+                .trim_end()
+                .to_owned(),
+        );
+        lines.push(format!(
+            "separator {}",
+            serde_json::to_string(alphabet.get_separator()).unwrap()
+        ));
+
+        for (directive, marks) in [
+            ("start", &self.start_states),
+            ("final", &self.finite_states),
+        ] {
+            let states: Vec<String> = Self::get_marked_states(marks)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            lines.push(
+                format!("{directive} {}", states.join(" "))
+                    .trim_end()
+                    .to_owned(),
+            );
+        }
+
+        for (from, label, to) in self.get_sorted_transitions() {
+            let label = match label {
+                Some(label) => Self::escape_token(alphabet.get_token(label)),
+                None => TEXT_EPSILON.to_owned(),
+            };
+            lines.push(format!("{from} {label} {to}"));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn from_text(text: &str) -> Result<(Self, Alphabet), FormatError> {
+        let mut automata: Option<Self> = None;
+        let mut alphabet = Alphabet::new("");
+        let mut separator: Option<String> = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let syntax_error = |message: &str| FormatError::Syntax {
+                line: line_number,
+                message: message.to_owned(),
+            };
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts[0] {
+                "states" => {
+                    if automata.is_some() {
+                        return Err(syntax_error("duplicate 'states' directive"));
+                    }
+                    let size = parts
+                        .get(1)
+                        .and_then(|size| size.parse().ok())
+                        .ok_or_else(|| syntax_error("expected number of states"))?;
+
+                    let mut result = Self::with_size(size)?;
+                    result.start_states[super::START] = false;
+                    automata = Some(result);
+                }
+                "alphabet" => {
+                    let tokens: Vec<&str> = parts[1..]
+                        .iter()
+                        .map(|token| Self::unescape_token(token))
+                        .collect();
+                    alphabet = Alphabet::with_inferred_separator(&tokens);
+                }
+                "separator" => {
+                    let value = line["separator".len()..].trim();
+                    separator = Some(
+                        serde_json::from_str(value)
+                            .map_err(|_| syntax_error("expected separator as JSON string"))?,
+                    );
+                }
+                "start" | "final" => {
+                    let result = automata
+                        .as_mut()
+                        .ok_or_else(|| syntax_error("'states' directive expected first"))?;
+                    let states = Self::parse_states(&parts[1..])
+                        .ok_or_else(|| syntax_error("expected list of states"))?;
+                    result.set_marked_states(&states, parts[0] == "start")?;
+                }
+                _ => {
+                    let result = automata
+                        .as_mut()
+                        .ok_or_else(|| syntax_error("'states' directive expected first"))?;
+                    if parts.len() != 3 {
+                        return Err(syntax_error("expected '<from> <label> <to>'"));
+                    }
+
+                    let states = Self::parse_states(&[parts[0], parts[2]])
+                        .ok_or_else(|| syntax_error("expected '<from> <label> <to>'"))?;
+                    let label = if parts[1] == TEXT_EPSILON {
+                        None
+                    } else {
+                        Some(
+                            alphabet
+                                .get_symbol(Self::unescape_token(parts[1]))
+                                .ok_or_else(|| FormatError::UnknownLabel(parts[1].to_owned()))?,
+                        )
+                    };
+                    result.add_checked_transition(states[0], label, states[1])?;
+                }
+            }
+        }
+
+        let automata = automata.ok_or(FormatError::Syntax {
+            line: text.lines().count(),
+            message: "missing 'states' directive".to_owned(),
+        })?;
+
+        if let Some(separator) = separator {
+            let tokens: Vec<&str> = alphabet
+                .symbols()
+                .map(|symbol| alphabet.get_token(symbol))
+                .collect();
+            let mut result = Alphabet::new(&separator);
+            for token in tokens {
+                result.intern(token);
+            }
+            alphabet = result;
+        }
+
+        Ok((automata, alphabet))
+    }
+
+    fn with_size(size: usize) -> Result<Self, FormatError> {
+        if size == 0 {
+            return Err(FormatError::InvalidState(0));
+        }
+
+        Ok(Self::new(size))
+    }
+
+    fn get_sorted_transitions(&self) -> Vec<(usize, Option<Symbol>, usize)> {
+        let mut transitions = Vec::new();
+
+        for (from, state_transitions) in self.transitions.iter().enumerate() {
+            for to in &state_transitions.epsilon {
+                transitions.push((from, None, *to));
+            }
+            for (label, next_states) in &state_transitions.labelled {
+                for to in next_states {
+                    transitions.push((from, Some(*label), *to));
+                }
+            }
+        }
+        transitions.sort();
+
+        transitions
+    }
+
+    fn get_marked_states(marks: &[bool]) -> Vec<usize> {
+        (0..marks.len()).filter(|state| marks[*state]).collect()
+    }
+
+    fn set_marked_states(&mut self, states: &[usize], is_start: bool) -> Result<(), FormatError> {
+        for state in states {
+            if *state >= self.size {
+                return Err(FormatError::InvalidState(*state));
+            }
+
+            if is_start {
+                self.start_states[*state] = true;
+            } else {
+                self.finite_states[*state] = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_checked_transition(
+        &mut self,
+        from: usize,
+        label: Option<Symbol>,
+        to: usize,
+    ) -> Result<(), FormatError> {
+        for state in [from, to] {
+            if state >= self.size {
+                return Err(FormatError::InvalidState(state));
+            }
+        }
+
+        match label {
+            Some(label) => self.add_transition(from, label, to),
+            None => self.add_epsilon_transition(from, to),
+        }
+
+        Ok(())
+    }
+
+    fn escape_token(token: &str) -> String {
+        if token == TEXT_EPSILON || token.starts_with(TEXT_ESCAPE) {
+            format!("{TEXT_ESCAPE}{token}")
+        } else {
+            token.to_owned()
+        }
+    }
+
+    fn unescape_token(token: &str) -> &str {
+        token.strip_prefix(TEXT_ESCAPE).unwrap_or(token)
+    }
+
+    fn parse_states(parts: &[&str]) -> Option<Vec<usize>> {
+        parts.iter().map(|part| part.parse().ok()).collect()
+    }
+}
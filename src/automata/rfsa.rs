@@ -0,0 +1,106 @@
+// Канонический RFSA (Denis, Lemay, Terlutte): состояния -- простые остаточные языки,
+// то есть непустые остатки, не равные объединению строго вложенных в них остатков.
+// Остатки языка -- состояния минимального полного ДКА.
+
+use crate::alphabet::Alphabet;
+
+use super::{AutomataImpl, START};
+
+impl AutomataImpl {
+    // Начальные состояния -- простые остатки, вложенные в язык. У пустого языка нет
+    // простых остатков, поэтому, как и minimize, возвращается одно отвергающее состояние
+    pub fn get_canonical_rfsa(&self, alphabet: &Alphabet) -> Self {
+        let residuals = self.minimize_impl().get_residuals(alphabet);
+        let prime_states: Vec<usize> = (0..residuals.len())
+            .filter(|state| residuals[*state].is_prime)
+            .collect();
+
+        if prime_states.is_empty() {
+            return Self::new(1);
+        }
+
+        let mut automata = Self::new(prime_states.len());
+        for (i, state) in prime_states.iter().enumerate() {
+            let residual = &residuals[*state];
+            automata.start_states[i] = residual.including[START];
+            automata.finite_states[i] = residual.is_final;
+
+            for letter in alphabet.symbols() {
+                let next_state = residual.next_states[letter];
+                for (j, prime_state) in prime_states.iter().enumerate() {
+                    if residuals[*prime_state].including[next_state] {
+                        automata.add_transition(i, letter, j);
+                    }
+                }
+            }
+        }
+
+        automata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alphabet::Alphabet;
+    use crate::automata::{Automata, AutomataImpl};
+    use crate::mat::automata_mat::AutomataMat;
+    use crate::nl::{CounterexampleStrategy, Nl, NlImpl};
+
+    // Канонический RFSA распознаёт язык, а NL* выучивает его с точностью до нумерации
+    // состояний: базисные префиксы гипотезы соответствуют простым остаткам
+    fn check_canonical_rfsa(target: &AutomataImpl, alphabet: &Alphabet, size: usize) {
+        let rfsa = target.get_canonical_rfsa(alphabet);
+        assert_eq!(rfsa.get_size(), size);
+        assert_eq!(rfsa.equivalent(target), None);
+
+        let mat = AutomataMat {
+            automata: target,
+            alphabet: alphabet.clone(),
+        };
+        let (hypothesis, _) = NlImpl::new(&mat)
+            .with_counterexample_strategy(CounterexampleStrategy::BinarySearch)
+            .get_hypothesis()
+            .unwrap();
+
+        assert_eq!(hypothesis.states.len(), rfsa.get_size());
+        assert_eq!(rfsa.equivalent(hypothesis.automata.as_ref()), None);
+    }
+
+    #[test]
+    fn canonical_rfsa_of_suffix_language() {
+        // Σ*aΣΣ: минимальный ДКА из 8 состояний, канонический RFSA из 4 простых остатков
+        let alphabet = Alphabet::from_chars("ab");
+        let (a, b) = (0, 1);
+        let mut target = AutomataImpl::new(4);
+        target.add_transition(0, a, 0);
+        target.add_transition(0, b, 0);
+        target.add_transition(0, a, 1);
+        for state in 1..3 {
+            target.add_transition(state, a, state + 1);
+            target.add_transition(state, b, state + 1);
+        }
+        target.finite_states[3] = true;
+
+        check_canonical_rfsa(&target, &alphabet, 4);
+    }
+
+    #[test]
+    fn canonical_rfsa_of_empty_language() {
+        let alphabet = Alphabet::from_chars("ab");
+        let target = AutomataImpl::new(1);
+
+        check_canonical_rfsa(&target, &alphabet, 1);
+    }
+
+    #[test]
+    fn canonical_rfsa_of_full_language() {
+        let alphabet = Alphabet::from_chars("ab");
+        let mut target = AutomataImpl::new(1);
+        for letter in alphabet.symbols() {
+            target.add_transition(0, letter, 0);
+        }
+        target.finite_states[0] = true;
+
+        check_canonical_rfsa(&target, &alphabet, 1);
+    }
+}
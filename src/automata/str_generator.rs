@@ -1,13 +1,15 @@
 use std::collections::VecDeque;
 
-use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::alphabet::{Alphabet, Symbol, Word};
 
 use super::{reachability::Reachability, AutomataImpl};
 
 pub struct StringGenerator<'a> {
     automata: &'a AutomataImpl,
     reachability: Reachability,
-    rng: ThreadRng,
+    rng: StdRng,
 }
 
 impl<'a> StringGenerator<'a> {
@@ -15,7 +17,7 @@ impl<'a> StringGenerator<'a> {
     const COMPLETE_WORD_PROBABILITY: f64 = 0.5;
 
     const EPSILON_CHAIN: [usize; 2] = [super::START; 2];
-    const EPSILON_WORDS: [String; 1] = [String::new(); 1];
+    const EPSILON_WORDS: [Word; 1] = [Word::new(); 1];
 
     const MUTATIONS_COUNT: usize = 6;
 
@@ -23,28 +25,72 @@ impl<'a> StringGenerator<'a> {
         Self {
             automata,
             reachability: Reachability::from_automata(automata),
-            rng: rand::thread_rng(),
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn from_automata_seeded(automata: &'a AutomataImpl, seed: u64) -> Self {
+        Self {
+            automata,
+            reachability: Reachability::from_automata(automata),
+            rng: StdRng::seed_from_u64(seed),
         }
     }
 
-    pub fn gen_strs(&mut self, count: usize) -> Vec<String> {
+    pub fn gen_strs(&mut self, count: usize) -> Vec<Word> {
         // Empty automata corner case
         if self.automata.is_empty() {
             return Vec::new();
         }
 
-        let mut strings = Vec::<String>::new();
+        let mut strings = Vec::<Word>::new();
 
         for _ in 0..count {
             let states = self.gen_states_chain();
-            let mut words = self.gen_words_chain(&states);
+            let words = self.gen_words_chain(&states);
 
-            strings.push(self.join_words(&words));
+            strings.push(words.concat());
         }
 
         strings
     }
 
+    // Вставка, удаление, замена буквы, перестановка соседних букв,
+    // повторение или обращение подслова
+    pub fn mutate_word(&mut self, word: &[Symbol], alphabet: &Alphabet) -> Word {
+        let mut word = word.to_vec();
+        if alphabet.is_empty() {
+            return word;
+        }
+
+        let letter = self.rng.gen_range(alphabet.symbols());
+        if word.is_empty() {
+            word.push(letter);
+            return word;
+        }
+
+        let position = self.rng.gen_range(0..word.len());
+        let end = self.rng.gen_range(position..word.len()) + 1;
+        match self.rng.gen_range(0..Self::MUTATIONS_COUNT) {
+            0 => word.insert(self.rng.gen_range(0..=word.len()), letter),
+            1 => {
+                word.remove(position);
+            }
+            2 => word[position] = letter,
+            3 if word.len() > 1 => {
+                let position = self.rng.gen_range(0..word.len() - 1);
+                word.swap(position, position + 1);
+            }
+            3 | 4 => {
+                let factor = word[position..end].to_vec();
+                word.splice(end..end, factor);
+            }
+            _ => word[position..end].reverse(),
+        }
+
+        word
+    }
+
     fn gen_states_chain(&mut self) -> Vec<usize> {
         let mut states = Vec::<usize>::new();
         states.push(super::START);
@@ -62,51 +108,57 @@ impl<'a> StringGenerator<'a> {
             let next_state = self.reachability.as_outcoming[current_state]
                 .choose(&mut self.rng)
                 .unwrap();
-            states.push(next_state.clone());
-            current_state = next_state.clone();
+            states.push(*next_state);
+            current_state = *next_state;
         }
 
         // Epsilon corner case
-        if current_state.eq(&super::START) {
+        if states.len() == 1 {
             return Vec::from(Self::EPSILON_CHAIN);
         }
 
         states
     }
 
-    fn gen_words_chain(&mut self, states_chain: &Vec<usize>) -> Vec<String> {
+    fn gen_words_chain(&mut self, states_chain: &Vec<usize>) -> Vec<Word> {
         if states_chain.eq(&Self::EPSILON_CHAIN) {
             return Self::EPSILON_WORDS.to_vec();
         }
 
-        let mut words_chain = Vec::<String>::with_capacity(states_chain.len() - 1);
+        let mut words_chain = Vec::<Word>::with_capacity(states_chain.len() - 1);
 
         let mut first_iter = states_chain.iter();
-        let mut second_iter = states_chain.iter().skip(1);
+        let second_iter = states_chain.iter().skip(1);
 
-        while let Some(to) = second_iter.next() {
+        for to in second_iter {
             let from = first_iter.next().unwrap();
 
-            words_chain.push(self.gen_word(&from, &to));
+            words_chain.push(self.gen_word(from, to));
             // NOTE: можно генерировать несколько слов на отрезке.
         }
 
         words_chain
     }
 
-    fn gen_word(&mut self, from: &usize, to: &usize) -> String {
-        let mut states_deq = VecDeque::<(String, usize)>::new();
-        states_deq.push_back((String::new(), from.clone()));
+    fn gen_word(&mut self, from: &usize, to: &usize) -> Word {
+        let mut states_deq = VecDeque::<(Word, usize)>::new();
+        states_deq.push_back((Word::new(), *from));
 
         while let Some((word_prefix, state)) = states_deq.pop_front() {
-            let mut outcoming = Vec::<(String, usize)>::new();
-            for (i, letters) in self.automata.transitions[state].iter().enumerate() {
-                if !self.reachability.as_incoming[*to].contains(&i) && to.ne(&i) {
-                    continue;
+            let transitions = &self.automata.transitions[state];
+            let mut outcoming = Vec::<(Word, usize)>::new();
+            for i in &transitions.epsilon {
+                if self.reachability.as_incoming[*to].contains(i) || to.eq(i) {
+                    outcoming.push((word_prefix.clone(), *i));
                 }
-
-                for letter in letters {
-                    outcoming.push((word_prefix.clone() + &letter.to_string(), i));
+            }
+            for (letter, next_states) in &transitions.labelled {
+                for i in next_states {
+                    if self.reachability.as_incoming[*to].contains(i) || to.eq(i) {
+                        let mut word = word_prefix.clone();
+                        word.push(*letter);
+                        outcoming.push((word, *i));
+                    }
                 }
             }
 
@@ -123,14 +175,4 @@ impl<'a> StringGenerator<'a> {
 
         unreachable!()
     }
-
-    fn join_words(&self, words: &Vec<String>) -> String {
-        let mut result = String::new();
-
-        for word in words.iter() {
-            result.push_str(word);
-        }
-
-        result
-    }
 }
@@ -0,0 +1,490 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::str::FromStr;
+use std::time::Duration;
+
+use bnf::Grammar;
+
+use crate::alphabet::{Alphabet, Word};
+use crate::automata::conformance::ConformanceMethod;
+use crate::automata::{Automata, AutomataImpl};
+use crate::config::{
+    get_alphabet, CONFORMANCE_EXTRA_STATES, EQUIVALENCE_TESTS, ORACLE_JOBS, ORACLE_RETRIES,
+    ORACLE_RETRY_DELAY_MS, PUMP_TESTS, RANDOM_MUTATIONS, RANDOM_SAMPLES, RANDOM_SEED, WORD_MAX_LEN,
+};
+use crate::grammars::{cfg::CFG, GrammarMat};
+use crate::mat::caching_mat::CachingMat;
+use crate::mat::conformance_testing::ConformanceTesting;
+use crate::mat::{random_testing::RandomTesting, EquivalenceMode, Mat, MatScript, RetryPolicy};
+use crate::nl::kearns_vazirani::KearnsVaziraniImpl;
+use crate::nl::lstar::LStarImpl;
+use crate::nl::observer::JsonLinesLogger;
+use crate::nl::{CounterexampleStrategy, Nl, NlImpl};
+use crate::pump::{Pumper, PumperImpl, ShatteredWord};
+
+pub const USAGE: &str = "\
+Usage: tfl-lab3 <command> [options] [arguments]
+
+Commands:
+  learn   (--oracle PATH | --grammar FILE)    learn a DFA with NL*, L* or Kearns–Vazirani
+  cnf     FILE                                convert a grammar to Chomsky normal form
+  prefix  FILE                                build the grammar of prefixes
+  invert  FILE                                build the grammar of reversed words
+  check   (--oracle PATH | --grammar FILE | --automata FILE) WORD
+                                              test membership of a word
+  pump    (--oracle PATH | --grammar FILE) W1 W2 W3 W4 W5
+                                              pump W2 and W4 simultaneously
+  help                                        show this message
+
+Options:
+  --alphabet LETTERS    alphabet of single-letter symbols (default: grammar terminals or abc)
+  --tokens \"T1 T2 ...\"  alphabet of multi-letter tokens, words are separated by spaces
+  --equivalence MODE    equivalence testing: exhaustive, random, w or wp (default: exhaustive)
+  --max-tests N         exhaustive: words tested per query, each query starts over from
+                        the shortest word (default: 1000)
+  --max-len N           exhaustive: maximal length of a test word (default: 10)
+  --samples N           random: words sampled from the hypothesis and its complement (default: 50)
+  --mutations N         random: mutations of each sampled word (default: 4)
+  --seed N              random: seed of the word generator (default: 0)
+  --extra-states N      w/wp: assumed bound on missing hypothesis states (default: 1)
+  --learner NAME        learn: nl (NL*), lstar (L*) or kv (Kearns–Vazirani) (default: nl)
+  --counterexamples S   nl: counterexample handling: prefixes, suffixes or binary (default: binary)
+  --tests N             pumping iterations (default: 10)
+  --format FORMAT       learn: text, json or dot (default: text); cnf/prefix/invert: pretty or bnf
+  --hypothesis          learn: print the hypothesis itself (the residual NFA for nl), not its DFA
+  --annotate            learn: with --format dot, draw the hypothesis with basic prefixes
+  --report              learn: print query statistics and timings to stderr
+  --log FILE            nl: write NL* events to FILE, one JSON object per line
+  --batch               keep one oracle process running and send it words on stdin
+  --jobs N              run up to N oracle processes at once, without --batch (default: 1)
+  --timeout MS          fail an oracle query that takes longer than MS milliseconds
+  --retries N           repeat a failed oracle query up to N times (default: 0)
+  --retry-delay MS      pause between retries in milliseconds (default: 100)
+  --cache FILE          keep membership answers in FILE and reuse them in later runs
+  --output FILE         write the result to FILE instead of stdout
+
+Grammar files contain one rule per line, e.g. `S -> aSb | 'if'A |`.
+Automata files use the JSON or text format described in src/automata/format.rs.";
+
+const FLAGS: [&str; 4] = ["annotate", "batch", "hypothesis", "report"];
+
+// Опции, которые принимает каждая команда; остальные считаются опечатками
+const MAT_OPTIONS: [&str; 17] = [
+    "oracle",
+    "grammar",
+    "alphabet",
+    "tokens",
+    "equivalence",
+    "max-tests",
+    "max-len",
+    "samples",
+    "mutations",
+    "seed",
+    "extra-states",
+    "batch",
+    "jobs",
+    "timeout",
+    "retries",
+    "retry-delay",
+    "cache",
+];
+const LEARN_OPTIONS: [&str; 8] = [
+    "learner",
+    "counterexamples",
+    "format",
+    "hypothesis",
+    "annotate",
+    "report",
+    "log",
+    "output",
+];
+const TRANSFORM_OPTIONS: [&str; 2] = ["format", "output"];
+
+struct Arguments {
+    options: HashMap<String, String>,
+    positional: Vec<String>,
+}
+
+impl Arguments {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = HashMap::new();
+        let mut positional = Vec::new();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                positional.push(arg.to_owned());
+                continue;
+            };
+
+            if let Some((name, value)) = name.split_once('=') {
+                options.insert(name.to_owned(), value.to_owned());
+            } else if FLAGS.contains(&name) {
+                options.insert(name.to_owned(), String::new());
+            } else {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("option --{name} requires a value"))?;
+                options.insert(name.to_owned(), value.to_owned());
+            }
+        }
+
+        Ok(Self {
+            options,
+            positional,
+        })
+    }
+
+    fn check_options(&self, known_options: &[&[&str]]) -> Result<(), String> {
+        let mut names: Vec<&String> = self.options.keys().collect();
+        names.sort();
+
+        for name in names {
+            if !known_options
+                .iter()
+                .any(|options| options.contains(&name.as_str()))
+            {
+                return Err(format!("unknown option --{name}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    fn get_number<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.get(name) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("option --{name} expects a number, got '{value}'")),
+            None => Ok(default),
+        }
+    }
+
+    fn get_equivalence_mode(&self) -> Result<EquivalenceMode, String> {
+        match self.get("equivalence").unwrap_or("exhaustive") {
+            "exhaustive" => Ok(EquivalenceMode::Exhaustive),
+            "random" => Ok(EquivalenceMode::Random(RandomTesting {
+                samples: self.get_number("samples", RANDOM_SAMPLES)?,
+                mutations: self.get_number("mutations", RANDOM_MUTATIONS)?,
+                seed: self.get_number("seed", RANDOM_SEED)?,
+            })),
+            mode @ ("w" | "wp") => Ok(EquivalenceMode::Conformance(ConformanceTesting {
+                method: if mode == "w" {
+                    ConformanceMethod::W
+                } else {
+                    ConformanceMethod::Wp
+                },
+                extra_states: self.get_number("extra-states", CONFORMANCE_EXTRA_STATES)?,
+            })),
+            mode => Err(format!("unknown equivalence mode '{mode}'")),
+        }
+    }
+
+    fn get_counterexample_strategy(&self) -> Result<CounterexampleStrategy, String> {
+        match self.get("counterexamples").unwrap_or("binary") {
+            "prefixes" => Ok(CounterexampleStrategy::AllPrefixes),
+            "suffixes" => Ok(CounterexampleStrategy::AllSuffixes),
+            "binary" => Ok(CounterexampleStrategy::BinarySearch),
+            strategy => Err(format!("unknown counterexample strategy '{strategy}'")),
+        }
+    }
+
+    fn get_positional(&self, count: usize) -> Result<&[String], String> {
+        if self.positional.len() != count {
+            return Err(format!(
+                "expected {count} argument(s), got {}",
+                self.positional.len()
+            ));
+        }
+
+        Ok(&self.positional)
+    }
+
+    fn get_alphabet(&self, default: Alphabet) -> Alphabet {
+        if let Some(tokens) = self.get("tokens") {
+            let tokens: Vec<&str> = tokens.split_whitespace().collect();
+            return Alphabet::from_tokens(&tokens);
+        }
+
+        match self.get("alphabet") {
+            Some(letters) => Alphabet::from_chars(letters),
+            None => default,
+        }
+    }
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let Some((command, args)) = args.split_first() else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+    let args = Arguments::parse(args)?;
+
+    match command.as_str() {
+        "learn" => learn(&args),
+        "cnf" | "prefix" | "invert" => transform(command, &args),
+        "check" => check(&args),
+        "pump" => pump(&args),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        _ => Err(format!("unknown command '{command}', see 'tfl-lab3 help'")),
+    }
+}
+
+fn learn(args: &Arguments) -> Result<(), String> {
+    args.check_options(&[&MAT_OPTIONS, &LEARN_OPTIONS])?;
+    args.get_positional(0)?;
+
+    with_mat(args, &|mat| {
+        let mut learner: Box<dyn Nl + '_> = match args.get("learner").unwrap_or("nl") {
+            "nl" => Box::new(new_nl(args, mat)?),
+            "lstar" => Box::new(LStarImpl::new(mat).map_err(|error| error.to_string())?),
+            "kv" => Box::new(KearnsVaziraniImpl::new(mat).map_err(|error| error.to_string())?),
+            learner => return Err(format!("unknown learner '{learner}'")),
+        };
+        let (hypothesis, report) = learner
+            .get_hypothesis()
+            .map_err(|error| error.to_string())?;
+        if args.has("report") {
+            eprintln!("{report}");
+        }
+        let alphabet = mat.get_alphabet();
+        let format = args.get("format").unwrap_or("text");
+
+        // Подписи относятся к состояниям гипотезы, поэтому с ними выводится она сама
+        let dfa;
+        let automata = if args.has("hypothesis") || (format == "dot" && args.has("annotate")) {
+            hypothesis.automata.as_ref()
+        } else {
+            dfa = hypothesis.determinize();
+            dfa.as_ref()
+        };
+        let automata = automata.as_any().downcast_ref::<AutomataImpl>().unwrap();
+
+        let output = match format {
+            "text" => automata.to_text(alphabet),
+            "json" => automata.to_json(alphabet) + "\n",
+            "dot" if args.has("annotate") => {
+                let annotations = hypothesis.get_state_annotations(alphabet);
+                automata.to_dot(alphabet, Some(&annotations))
+            }
+            "dot" => automata.to_dot(alphabet, None),
+            format => return Err(format!("unknown automata format '{format}'")),
+        };
+
+        write_output(args, &output)
+    })
+}
+
+fn new_nl<'a>(args: &Arguments, mat: &'a dyn Mat) -> Result<NlImpl<'a>, String> {
+    let mut nl = NlImpl::new(mat).with_counterexample_strategy(args.get_counterexample_strategy()?);
+    if let Some(path) = args.get("log") {
+        let file = File::create(path).map_err(|error| format!("{path}: {error}"))?;
+        let logger = JsonLinesLogger::new(BufWriter::new(file), mat.get_alphabet().clone());
+        nl = nl.with_observer(Box::new(logger));
+    }
+
+    Ok(nl)
+}
+
+fn transform(command: &str, args: &Arguments) -> Result<(), String> {
+    args.check_options(&[&TRANSFORM_OPTIONS])?;
+    let path = &args.get_positional(1)?[0];
+    let mut cfg = read_grammar(path)?;
+
+    // Построение префиксной и обращённой грамматик предполагает НФХ
+    cfg.to_cnf();
+    let mut cfg = match command {
+        "prefix" => cfg.to_prefix_grammar(),
+        "invert" => cfg.to_inverted(),
+        _ => cfg,
+    };
+    cfg.to_cnf();
+
+    let output = match args.get("format").unwrap_or("pretty") {
+        "pretty" => cfg.to_pretty_string(),
+        "bnf" => cfg.to_bnf(),
+        format => return Err(format!("unknown grammar format '{format}'")),
+    };
+
+    write_output(args, &output)
+}
+
+fn check(args: &Arguments) -> Result<(), String> {
+    args.check_options(&[&MAT_OPTIONS, &["automata"]])?;
+    let text = args.get_positional(1)?[0].to_owned();
+
+    if let Some(path) = args.get("automata") {
+        if args.has("oracle") || args.has("grammar") {
+            return Err("--automata cannot be used with --oracle or --grammar".to_owned());
+        }
+        // Символы переходов заданы алфавитом из файла, другой алфавит их перепутает
+        if args.has("alphabet") || args.has("tokens") {
+            return Err("--alphabet and --tokens cannot be used with --automata".to_owned());
+        }
+
+        let (automata, alphabet) = read_automata(path)?;
+        let word = parse_word(&alphabet, &text)?;
+        println!("{}", automata.check_membership(&word) as u8);
+        return Ok(());
+    }
+
+    with_mat(args, &|mat| {
+        let word = parse_word(mat.get_alphabet(), &text)?;
+        let is_member = mat
+            .check_membership(&word)
+            .map_err(|error| error.to_string())?;
+        println!("{}", is_member as u8);
+        Ok(())
+    })
+}
+
+fn pump(args: &Arguments) -> Result<(), String> {
+    args.check_options(&[&MAT_OPTIONS, &["tests"]])?;
+    let parts = args.get_positional(5)?.to_owned();
+    let tests = args.get_number("tests", PUMP_TESTS)?;
+
+    with_mat(args, &|mat| {
+        let words = parts
+            .iter()
+            .map(|part| parse_word(mat.get_alphabet(), part))
+            .collect::<Result<Vec<Word>, String>>()?;
+        let word = ShatteredWord {
+            w1: words[0].to_owned(),
+            w2: words[1].to_owned(),
+            w3: words[2].to_owned(),
+            w4: words[3].to_owned(),
+            w5: words[4].to_owned(),
+        };
+
+        let pumper = PumperImpl { mat, tests };
+        let is_pumped = pumper.pump_word(&word).map_err(|error| error.to_string())?;
+        println!("{}", is_pumped as u8);
+        Ok(())
+    })
+}
+
+// Строит оракул по параметрам командной строки и передаёт его в action
+fn with_mat(
+    args: &Arguments,
+    action: &dyn Fn(&dyn Mat) -> Result<(), String>,
+) -> Result<(), String> {
+    let max_tests = args.get_number("max-tests", EQUIVALENCE_TESTS)?;
+    let word_max_len = args.get_number("max-len", WORD_MAX_LEN)?;
+    let equivalence_mode = args.get_equivalence_mode()?;
+
+    match (args.get("oracle"), args.get("grammar")) {
+        (Some(oracle_path), None) => {
+            let alphabet = args.get_alphabet(get_alphabet());
+            let mut mat = MatScript::new(alphabet, oracle_path, max_tests, word_max_len)
+                .with_equivalence_mode(equivalence_mode);
+            if args.has("batch") {
+                mat = mat.with_batch_mode();
+            }
+            mat = mat.with_workers(args.get_number("jobs", ORACLE_JOBS)?);
+            if args.has("timeout") {
+                let timeout = args.get_number("timeout", 0)?;
+                mat = mat.with_timeout(Duration::from_millis(timeout as u64));
+            }
+            mat = mat.with_retry_policy(RetryPolicy {
+                attempts: args.get_number("retries", ORACLE_RETRIES)? + 1,
+                delay: Duration::from_millis(
+                    args.get_number("retry-delay", ORACLE_RETRY_DELAY_MS)? as u64,
+                ),
+            });
+            with_cache(args, &mat, action)
+        }
+        (None, Some(path)) => {
+            let mut cfg = read_grammar(path)?;
+            let alphabet = args.get_alphabet(cfg.get_alphabet());
+
+            cfg.to_cnf();
+            let grammar: Grammar = cfg
+                .to_bnf()
+                .parse()
+                .map_err(|error| format!("{path}: {error}"))?;
+
+            let mat = GrammarMat {
+                grammar: &grammar,
+                alphabet,
+                max_tests,
+                word_max_len,
+                equivalence_mode,
+            };
+            with_cache(args, &mat, action)
+        }
+        _ => Err("exactly one of --oracle and --grammar is required".to_owned()),
+    }
+}
+
+// Ответы оракула всегда запоминаются в памяти, а с --cache ещё и в файле
+fn with_cache(
+    args: &Arguments,
+    mat: &dyn Mat,
+    action: &dyn Fn(&dyn Mat) -> Result<(), String>,
+) -> Result<(), String> {
+    let Some(path) = args.get("cache") else {
+        return action(&CachingMat::new(mat));
+    };
+
+    let mat = CachingMat::with_store(mat, path).map_err(|error| format!("{path}: {error}"))?;
+    let result = action(&mat);
+
+    let statistics = mat.get_statistics();
+    eprintln!(
+        "cache: {} hits, {} misses",
+        statistics.hits, statistics.misses
+    );
+
+    result
+}
+
+fn read_grammar(path: &str) -> Result<CFG, String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("{path}: {error}"))?;
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    Ok(CFG::parse(lines))
+}
+
+fn read_automata(path: &str) -> Result<(AutomataImpl, Alphabet), String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("{path}: {error}"))?;
+
+    let result = if text.trim_start().starts_with('{') {
+        AutomataImpl::from_json(&text)
+    } else {
+        AutomataImpl::from_text(&text)
+    };
+
+    result.map_err(|error| format!("{path}: {error}"))
+}
+
+fn parse_word(alphabet: &Alphabet, text: &str) -> Result<Word, String> {
+    alphabet
+        .parse_word(text)
+        .ok_or_else(|| format!("word '{text}' is not over the alphabet"))
+}
+
+fn write_output(args: &Arguments, output: &str) -> Result<(), String> {
+    match args.get("output") {
+        Some(path) => fs::write(path, output).map_err(|error| format!("{path}: {error}")),
+        None => {
+            print!("{output}");
+            Ok(())
+        }
+    }
+}
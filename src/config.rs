@@ -1,18 +1,20 @@
-use std::collections::HashSet;
+use crate::alphabet::{Alphabet, Symbol};
 
 pub const ALPHABET: &str = "abc";
-pub const EPSILON: &str = "";
+pub const EPSILON: &[Symbol] = &[];
 
-pub const EQUIVALENCE_TESTS: usize = 10;
+pub const EQUIVALENCE_TESTS: usize = 1000;
+pub const WORD_MAX_LEN: usize = 10;
 pub const REGULARITY_TESTS: usize = 10;
 pub const PUMP_TESTS: usize = 10;
+pub const RANDOM_SAMPLES: usize = 50;
+pub const RANDOM_MUTATIONS: usize = 4;
+pub const RANDOM_SEED: u64 = 0;
+pub const CONFORMANCE_EXTRA_STATES: usize = 1;
+pub const ORACLE_JOBS: usize = 1;
+pub const ORACLE_RETRIES: usize = 0;
+pub const ORACLE_RETRY_DELAY_MS: usize = 100;
 
-pub fn get_alphabet_as_hashset() -> HashSet<String> {
-    let mut alphabet = HashSet::<String>::with_capacity(ALPHABET.len());
-
-    for label in ALPHABET.chars() {
-        alphabet.insert(label.to_string());
-    }
-
-    alphabet
+pub fn get_alphabet() -> Alphabet {
+    Alphabet::from_chars(ALPHABET)
 }
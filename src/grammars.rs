@@ -3,27 +3,54 @@
 use bnf::Grammar;
 
 use crate::{
+    alphabet::{Alphabet, Symbol},
     automata::Automata,
-    mat::{EquivalenceCheckResult, Mat},
+    mat::{
+        check_equivalence_with_mode, enumerator::WordEnumerator, EquivalenceCheckResult,
+        EquivalenceMode, Mat, MatError,
+    },
 };
 
 pub mod cfg;
 
 pub struct GrammarMat<'a> {
     pub grammar: &'a Grammar,
+    pub alphabet: Alphabet,
+    pub max_tests: usize,
+    pub word_max_len: usize,
+    pub equivalence_mode: EquivalenceMode,
 }
 
 impl<'a> Mat for GrammarMat<'a> {
-    fn check_membership(&self, word: &str) -> bool {
-        let mut tree = self.grammar.parse_input(word);
-        tree.next().is_some()
+    fn check_membership(&self, word: &[Symbol]) -> Result<bool, MatError> {
+        let word = self.alphabet.concat_word(word);
+        let mut tree = self.grammar.parse_input(&word);
+        Ok(tree.next().is_some())
     }
 
-    fn check_equivalence(&self, automata: &dyn Automata) -> EquivalenceCheckResult {
-        todo!()
+    fn check_equivalence(
+        &self,
+        automata: &dyn Automata,
+    ) -> Result<EquivalenceCheckResult, MatError> {
+        self.check_equivalence_using(self, automata)
     }
 
-    fn get_alphabet(&self) -> String {
-        todo!()
+    fn check_equivalence_using(
+        &self,
+        membership: &dyn Mat,
+        automata: &dyn Automata,
+    ) -> Result<EquivalenceCheckResult, MatError> {
+        check_equivalence_with_mode(
+            membership,
+            automata,
+            &self.equivalence_mode,
+            &mut WordEnumerator::new(self.alphabet.len()),
+            self.max_tests,
+            self.word_max_len,
+        )
+    }
+
+    fn get_alphabet(&self) -> &Alphabet {
+        &self.alphabet
     }
 }
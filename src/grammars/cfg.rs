@@ -1,12 +1,14 @@
 use std::collections::{BTreeSet, HashMap};
 
-type Symbol = String;
-type Terminal = String;
-type NonTerminal = String;
+use crate::alphabet::{Alphabet, Symbol, Word};
+
+// Терминалы и нетерминалы -- символы общей таблицы имён symbols
+type Terminal = Symbol;
+type NonTerminal = Symbol;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Production {
-    pub elements: Vec<Symbol>,
+    pub elements: Word,
 }
 
 impl Production {
@@ -17,6 +19,7 @@ impl Production {
 
 #[derive(Debug)]
 pub struct CFG {
+    pub symbols: Alphabet,
     pub non_terminals: BTreeSet<NonTerminal>,
     pub terminals: BTreeSet<Terminal>,
     pub productions: HashMap<NonTerminal, Vec<Production>>,
@@ -26,12 +29,14 @@ pub struct CFG {
 // TODO: buy one more hqd and split this impl into several impls
 impl CFG {
     pub fn new(
+        symbols: Alphabet,
         non_terminals: BTreeSet<NonTerminal>,
         terminals: BTreeSet<Terminal>,
         productions: HashMap<NonTerminal, Vec<Production>>,
         start_symbol: NonTerminal,
     ) -> CFG {
         CFG {
+            symbols,
             non_terminals,
             terminals,
             productions,
@@ -42,15 +47,16 @@ impl CFG {
     fn add_production(&mut self, non_terminal: NonTerminal, production: Production) {
         self.productions
             .entry(non_terminal)
-            .or_insert(Vec::new())
+            .or_default()
             .push(production);
     }
 
     pub fn parse(lines: Vec<&str>) -> CFG {
+        let mut symbols = Alphabet::new("");
         let mut non_terminals = BTreeSet::new();
         let mut terminals = BTreeSet::new();
         let mut productions = HashMap::new();
-        let mut start_symbol = String::new();
+        let mut start_symbol: Option<NonTerminal> = None;
 
         for line in lines {
             let parts: Vec<&str> = line.split("->").collect();
@@ -58,44 +64,68 @@ impl CFG {
                 eprintln!("Неверный формат строки: {}", line);
                 continue;
             }
-            let lhs = parts[0].trim().to_string(); // Левая часть продукции (нетерминал)
+            let lhs = symbols.intern(parts[0].trim()); // Левая часть продукции (нетерминал)
             let rhs = parts[1].trim(); // Правая часть продукции
 
-            if start_symbol.is_empty() {
-                start_symbol = lhs.clone();
+            if start_symbol.is_none() {
+                start_symbol = Some(lhs);
             }
 
-            non_terminals.insert(lhs.clone());
+            non_terminals.insert(lhs);
 
             let production_parts: Vec<&str> = rhs.split('|').collect();
             for prod_part in production_parts {
-                let mut elements: Vec<Symbol> = Vec::new();
-
-                for ch in prod_part.trim().chars() {
-                    let sym = ch.to_string();
-                    if ch.is_uppercase() {
-                        // Если символ в верхнем регистре, добавляем как нетерминал
-                        if !non_terminals.contains(&sym) {
-                            non_terminals.insert(sym.clone());
+                let mut elements = Word::new();
+
+                let mut chars = prod_part.trim().chars();
+                while let Some(ch) = chars.next() {
+                    if ch == '\'' {
+                        // Терминал из нескольких символов записывается в кавычках: 'if'
+                        let name: String = chars.by_ref().take_while(|ch| *ch != '\'').collect();
+                        if !name.is_empty() {
+                            let sym = symbols.intern(&name);
+                            terminals.insert(sym);
+                            elements.push(sym);
                         }
+                    } else if ch.is_uppercase() {
+                        // Если символ в верхнем регистре, добавляем как нетерминал
+                        let sym = symbols.intern(&ch.to_string());
+                        non_terminals.insert(sym);
                         elements.push(sym);
                     } else if ch.is_lowercase() {
                         // Если символ в нижнем регистре, добавляем как терминал
-                        if !terminals.contains(&sym) {
-                            terminals.insert(sym.clone());
-                        }
+                        let sym = symbols.intern(&ch.to_string());
+                        terminals.insert(sym);
                         elements.push(sym);
                     }
                 }
 
                 productions
-                    .entry(lhs.clone())
+                    .entry(lhs)
                     .or_insert_with(Vec::new)
                     .push(Production { elements });
             }
         }
 
-        CFG::new(non_terminals, terminals, productions, start_symbol)
+        // Стартовый символ пустой грамматики -- нетерминал с пустым именем
+        let start_symbol = start_symbol.unwrap_or_else(|| symbols.intern(""));
+
+        CFG::new(symbols, non_terminals, terminals, productions, start_symbol)
+    }
+
+    // Терминалы упорядочены по именам
+    pub fn get_alphabet(&self) -> Alphabet {
+        let mut terminals: Vec<&str> = self
+            .terminals
+            .iter()
+            .map(|terminal| self.symbols.get_token(*terminal))
+            .collect();
+        terminals.sort_unstable();
+        Alphabet::with_inferred_separator(&terminals)
+    }
+
+    fn get_name(&self, symbol: Symbol) -> &str {
+        self.symbols.get_token(symbol)
     }
 
     pub fn to_bnf(&self) -> String {
@@ -104,7 +134,7 @@ impl CFG {
         // Отдельно обрабатываем стартовый символ
         if let Some(start_productions) = self.productions.get(&self.start_symbol) {
             bnf_representation
-                .push_str(&self.format_productions_to_bnf(&self.start_symbol, start_productions));
+                .push_str(&self.format_productions_to_bnf(self.start_symbol, start_productions));
         }
 
         // Получаем все нетерминалы, кроме стартового, и сортируем их алфавитно
@@ -113,12 +143,12 @@ impl CFG {
             .iter()
             .filter(|nt| **nt != self.start_symbol)
             .collect();
-        non_terminal_list.sort_unstable();
+        non_terminal_list.sort_unstable_by_key(|nt| self.get_name(**nt));
 
         // Формируем строки правил для остальных нетерминалов в алфавитном порядке
         for nt in non_terminal_list {
             if let Some(rhs_list) = self.productions.get(nt) {
-                bnf_representation.push_str(&self.format_productions_to_bnf(nt, rhs_list));
+                bnf_representation.push_str(&self.format_productions_to_bnf(*nt, rhs_list));
             }
         }
 
@@ -126,17 +156,22 @@ impl CFG {
     }
 
     // Вспомогательная функция для форматирования продукций в строку БНФ
-    fn format_productions_to_bnf(&self, nt: &NonTerminal, rhs_list: &Vec<Production>) -> String {
+    fn format_productions_to_bnf(&self, nt: NonTerminal, rhs_list: &[Production]) -> String {
         let mut rhs_strings: Vec<String> = rhs_list
             .iter()
             .map(|rhs| {
+                // Пустая продукция записывается пустым терминалом
+                if rhs.is_epsilon() {
+                    return "''".to_owned();
+                }
+
                 rhs.elements
                     .iter()
                     .map(|symbol| {
                         if self.non_terminals.contains(symbol) {
-                            format!("<{}>", symbol)
+                            format!("<{}>", self.get_name(*symbol))
                         } else {
-                            format!("'{}'", symbol)
+                            format!("'{}'", self.get_name(*symbol))
                         }
                     })
                     .collect::<Vec<_>>()
@@ -146,7 +181,7 @@ impl CFG {
 
         // Сортируем список правил для нетерминала
         rhs_strings.sort_unstable();
-        format!("<{}> ::= {}\n", nt, rhs_strings.join(" | "))
+        format!("<{}> ::= {}\n", self.get_name(nt), rhs_strings.join(" | "))
     }
 
     pub fn to_pretty_string(&self) -> String {
@@ -156,7 +191,7 @@ impl CFG {
         let prod_to_string = |prods: &Vec<Production>| -> String {
             prods
                 .iter()
-                .map(|prod| prod.elements.join(" "))
+                .map(|prod| self.get_names(&prod.elements).join(" "))
                 .collect::<Vec<_>>()
                 .join(" | ")
         };
@@ -164,19 +199,23 @@ impl CFG {
         // Сначала добавляем правила для начального символа грамматики
         if let Some(prods) = self.productions.get(&self.start_symbol) {
             let prods_string = prod_to_string(prods);
-            result.push_str(&format!("{} -> {}\n", self.start_symbol, prods_string));
+            result.push_str(&format!(
+                "{} -> {}\n",
+                self.get_name(self.start_symbol),
+                prods_string
+            ));
         }
 
         // Получаем ключи для нетерминалов и сортируем их, исключая начальный символ
         let mut non_terminals: Vec<_> = self.non_terminals.iter().collect();
-        non_terminals.sort_unstable();
+        non_terminals.sort_unstable_by_key(|nt| self.get_name(**nt));
         non_terminals.retain(|nt| **nt != self.start_symbol);
 
         // Добавляем правила для остальных нетерминалов в алфавитном порядке
         for nt in non_terminals {
             if let Some(prods) = self.productions.get(nt) {
                 let prods_string = prod_to_string(prods);
-                result.push_str(&format!("{} -> {}\n", nt, prods_string));
+                result.push_str(&format!("{} -> {}\n", self.get_name(*nt), prods_string));
             }
         }
 
@@ -208,11 +247,20 @@ impl CFG {
 
     // Вспомогательная функция для генерации нового уникального нетерминала
     fn next_non_terminal(&mut self) -> NonTerminal {
-        let new_nt = format!("S{}", self.non_terminals.len());
-        self.non_terminals.insert(new_nt.clone());
+        let new_nt = self
+            .symbols
+            .intern(&format!("S{}", self.non_terminals.len()));
+        self.non_terminals.insert(new_nt);
         new_nt
     }
 
+    fn get_names(&self, elements: &[Symbol]) -> Vec<&str> {
+        elements
+            .iter()
+            .map(|symbol| self.get_name(*symbol))
+            .collect()
+    }
+
     fn eliminate_long_rules(&mut self) {
         let mut new_productions = HashMap::new();
 
@@ -223,7 +271,7 @@ impl CFG {
                 // Делим правило, если оно длиннее двух символов
                 if prod.elements.len() > 2 {
                     let mut elements = prod.elements.clone();
-                    let mut current_nt = nt.clone();
+                    let mut current_nt = *nt;
                     while elements.len() > 2 {
                         // Создаём новые промежуточные правила
                         let first_elem = elements.remove(0);
@@ -234,7 +282,7 @@ impl CFG {
                             .entry(current_nt)
                             .or_insert_with(Vec::new)
                             .push(Production {
-                                elements: vec![first_elem, new_nt.clone()],
+                                elements: vec![first_elem, new_nt],
                             });
 
                         current_nt = new_nt;
@@ -250,7 +298,7 @@ impl CFG {
                 }
             }
             new_productions
-                .entry(nt.clone())
+                .entry(*nt)
                 .or_insert(Vec::<Production>::new())
                 .extend(transformed_prods);
         }
@@ -263,7 +311,7 @@ impl CFG {
 
         for (nt, productions) in &self.productions {
             if productions.iter().any(|p| p.is_epsilon()) {
-                eps_non_terminals.push(nt.clone());
+                eps_non_terminals.push(*nt);
             }
         }
 
@@ -289,7 +337,7 @@ impl CFG {
                     }
                 }
             }
-            new_productions.insert(nt.clone(), new_production_set);
+            new_productions.insert(*nt, new_production_set);
         }
 
         self.productions = new_productions;
@@ -309,14 +357,14 @@ impl CFG {
                 .iter()
                 .filter_map(|p| {
                     if p.elements.len() == 1 && self.non_terminals.contains(&p.elements[0]) {
-                        Some(p.elements[0].clone())
+                        Some(p.elements[0])
                     } else {
                         None
                     }
                 })
                 .collect();
 
-            chain_rules.insert(nt.clone(), production_symbols);
+            chain_rules.insert(*nt, production_symbols);
         }
 
         // Вычисление транзитивных замыканий для каждого нетерминала
@@ -327,7 +375,7 @@ impl CFG {
                 if let Some(next_rules) = chain_rules.get(&closure[index]) {
                     for next_rule in next_rules {
                         if !closure.contains(next_rule) {
-                            closure.push(next_rule.clone());
+                            closure.push(*next_rule);
                         }
                     }
                 }
@@ -360,15 +408,19 @@ impl CFG {
                 }
             }
 
-            new_productions.insert(nt.clone(), prod_set);
+            new_productions.insert(*nt, prod_set);
         }
 
-        self.productions = new_productions;
-
-        for prods in self.productions.values_mut() {
-            prods.sort_by(|a, b| a.elements.cmp(&b.elements));
+        // Продукции упорядочиваются по именам символов
+        for prods in new_productions.values_mut() {
+            prods.sort_by(|a, b| {
+                self.get_names(&a.elements)
+                    .cmp(&self.get_names(&b.elements))
+            });
             prods.dedup();
         }
+
+        self.productions = new_productions;
     }
 
     fn find_productive_non_terminals(&self) -> BTreeSet<NonTerminal> {
@@ -385,7 +437,7 @@ impl CFG {
                             .iter()
                             .all(|s| self.terminals.contains(s) || productive.contains(s))
                         {
-                            productive.insert(nt.clone());
+                            productive.insert(*nt);
                             changed = true;
                             break;
                         }
@@ -399,16 +451,16 @@ impl CFG {
     // Функция для определения достижимых нетерминалов
     fn find_reachable_non_terminals(&self) -> BTreeSet<NonTerminal> {
         let mut reachable: BTreeSet<NonTerminal> = BTreeSet::new();
-        let mut to_visit: Vec<NonTerminal> = vec![self.start_symbol.clone()];
+        let mut to_visit: Vec<NonTerminal> = vec![self.start_symbol];
 
         while let Some(nt) = to_visit.pop() {
             if !reachable.contains(&nt) {
-                reachable.insert(nt.clone());
+                reachable.insert(nt);
                 if let Some(prods) = self.productions.get(&nt) {
                     for prod in prods {
                         for symbol in &prod.elements {
                             if !reachable.contains(symbol) && self.non_terminals.contains(symbol) {
-                                to_visit.push(symbol.clone());
+                                to_visit.push(*symbol);
                             }
                         }
                     }
@@ -420,7 +472,7 @@ impl CFG {
 
     fn find_reachable_non_terminals2(&self) -> BTreeSet<NonTerminal> {
         let mut reachable: BTreeSet<NonTerminal> = BTreeSet::new(); // Шаг 0
-        reachable.insert(self.start_symbol.clone());
+        reachable.insert(self.start_symbol);
 
         let mut changed = true;
         while changed {
@@ -432,9 +484,7 @@ impl CFG {
                     for prod in prods {
                         // Шаг 1
                         for symbol in &prod.elements {
-                            if self.non_terminals.contains(symbol)
-                                && reachable.insert(symbol.clone())
-                            {
+                            if self.non_terminals.contains(symbol) && reachable.insert(*symbol) {
                                 changed = true; // Изменилось множество достижимых нетерминалов
                             }
                         }
@@ -477,9 +527,9 @@ impl CFG {
         let mut new_productions: HashMap<NonTerminal, Vec<Production>> = HashMap::new();
 
         // Вспомогательная функция для создания уникального имени для нового нетерминала
-        fn make_unique_non_terminal(terminal: &Terminal) -> NonTerminal {
-            let new_nt = format!("G{}", terminal);
-            new_nt
+        fn make_unique_non_terminal(symbols: &mut Alphabet, terminal: Terminal) -> NonTerminal {
+            let new_nt = format!("G{}", symbols.get_token(terminal));
+            symbols.intern(&new_nt)
         }
 
         // Обход всех продукций и замена терминалов на нетерминалы там, где это необходимо
@@ -494,31 +544,28 @@ impl CFG {
                 for symbol in &prod.elements {
                     if self.terminals.contains(symbol) && prod.elements.len() > 1 {
                         // Создаем новый нетерминал и добавляем правило Ui -> ui
-                        let new_nt = make_unique_non_terminal(symbol);
-                        self.non_terminals.insert(new_nt.clone());
+                        let new_nt = make_unique_non_terminal(&mut self.symbols, *symbol);
+                        self.non_terminals.insert(new_nt);
                         let new_rule = Production {
-                            elements: vec![symbol.clone()],
+                            elements: vec![*symbol],
                         };
                         if !new_productions
-                            .entry(new_nt.clone())
-                            .or_insert_with(Vec::new)
+                            .entry(new_nt)
+                            .or_default()
                             .contains(&new_rule)
                         {
-                            new_productions
-                                .entry(new_nt.clone())
-                                .or_insert_with(Vec::new)
-                                .push(new_rule);
+                            new_productions.entry(new_nt).or_default().push(new_rule);
                         }
                         new_prod.elements.push(new_nt);
                     } else {
-                        new_prod.elements.push(symbol.clone());
+                        new_prod.elements.push(*symbol);
                     }
                 }
 
                 new_prod_set.push(new_prod);
             }
 
-            new_productions.insert(nt.clone(), new_prod_set);
+            new_productions.insert(*nt, new_prod_set);
         }
 
         self.productions = new_productions;
@@ -526,8 +573,10 @@ impl CFG {
 
     // TODO: do i need it?
     fn add_new_start_symbol(&mut self) {
-        let original_start_symbol = self.start_symbol.clone();
-        let new_start_symbol = format!("S{}", self.non_terminals.len() + 1);
+        let original_start_symbol = self.start_symbol;
+        let new_start_symbol = self
+            .symbols
+            .intern(&format!("S{}", self.non_terminals.len() + 1));
 
         // Для каждого нетерминала проверяем, не используется ли оригинальный стартовый символ в правых частях
         let mut start_symbol_used = false;
@@ -546,10 +595,10 @@ impl CFG {
         // Если стартовый символ используется, добавляем новое стартовое правило
         if start_symbol_used {
             // Обновляем список нетерминалов
-            self.non_terminals.insert(new_start_symbol.clone());
+            self.non_terminals.insert(new_start_symbol);
             // Вставляем новую продукцию с новым стартовым символом, ведущим к оригинальному стартовому символу
             self.productions.insert(
-                new_start_symbol.clone(),
+                new_start_symbol,
                 vec![Production {
                     elements: vec![original_start_symbol],
                 }],
@@ -560,52 +609,60 @@ impl CFG {
     }
 
     pub fn to_prefix_grammar(&self) -> CFG {
+        let mut symbols = self.symbols.clone();
+        let start_symbol = get_epsilon_version(&mut symbols, self.start_symbol);
         let mut prefix_cfg = CFG {
+            symbols,
             non_terminals: self.non_terminals.clone(),
             terminals: self.terminals.clone(),
             productions: self.productions.clone(), // Все правила будут добавлены заново
-            start_symbol: format!("{}ε", self.start_symbol),
+            start_symbol,
         };
 
         // Добавляем "ε" версии нетерминалов и генерируем новые правила для префиксов
         for nt in &self.non_terminals {
-            let nt_epsilon = format!("{}ε", nt);
-            prefix_cfg.non_terminals.insert(nt_epsilon.clone());
+            let nt_epsilon = get_epsilon_version(&mut prefix_cfg.symbols, *nt);
+            prefix_cfg.non_terminals.insert(nt_epsilon);
 
             if let Some(prod_list) = self.productions.get(nt) {
                 for prod in prod_list {
                     if prod.elements.len() == 1 {
                         // В случае A -> a добавляем Aε -> a | ε
-                        let prod_epsilon = vec![prod.elements[0].clone()];
+                        let prod_epsilon = vec![prod.elements[0]];
                         prefix_cfg
                             .productions
-                            .entry(nt_epsilon.clone())
-                            .or_insert_with(Vec::new)
+                            .entry(nt_epsilon)
+                            .or_default()
                             .push(Production {
                                 elements: prod_epsilon,
                             });
                         prefix_cfg
                             .productions
-                            .entry(nt_epsilon.clone())
-                            .or_insert_with(Vec::new)
+                            .entry(nt_epsilon)
+                            .or_default()
                             .push(Production { elements: vec![] });
                     } else if prod.elements.len() == 2 {
                         // В случае A -> BC добавляем Aε -> BCε | Bε
-                        let prod_epsilon =
-                            vec![prod.elements[0].clone(), format!("{}ε", prod.elements[1])];
+                        let prod_epsilon = vec![
+                            prod.elements[0],
+                            get_epsilon_version(&mut prefix_cfg.symbols, prod.elements[1]),
+                        ];
                         prefix_cfg
                             .productions
-                            .entry(nt_epsilon.clone())
-                            .or_insert_with(Vec::new)
+                            .entry(nt_epsilon)
+                            .or_default()
                             .push(Production {
                                 elements: prod_epsilon,
                             });
 
-                        let single_prod_epsilon = vec![format!("{}ε", prod.elements[0])];
+                        let single_prod_epsilon = vec![get_epsilon_version(
+                            &mut prefix_cfg.symbols,
+                            prod.elements[0],
+                        )];
                         prefix_cfg
                             .productions
-                            .entry(nt_epsilon.clone())
-                            .or_insert_with(Vec::new)
+                            .entry(nt_epsilon)
+                            .or_default()
                             .push(Production {
                                 elements: single_prod_epsilon,
                             });
@@ -617,8 +674,8 @@ impl CFG {
         // Добавляем стартовое правило Sε -> ε
         prefix_cfg
             .productions
-            .entry(prefix_cfg.start_symbol.clone())
-            .or_insert_with(Vec::new)
+            .entry(prefix_cfg.start_symbol)
+            .or_default()
             .push(Production { elements: vec![] });
 
         prefix_cfg
@@ -627,10 +684,11 @@ impl CFG {
     pub fn to_inverted(&self) -> CFG {
         // Новая CFG с копированием терминалов и начальным символом из исходной грамматики
         let mut inverted_cfg = CFG {
+            symbols: self.symbols.clone(),
             non_terminals: self.non_terminals.clone(),
             terminals: self.terminals.clone(),
             productions: HashMap::new(),
-            start_symbol: self.start_symbol.clone(),
+            start_symbol: self.start_symbol,
         };
 
         for (nt, prods) in &self.productions {
@@ -643,7 +701,7 @@ impl CFG {
                 // Добавляем правило в новую грамматику, сохраняя правила A -> a и S -> ε без изменений
                 inverted_cfg
                     .productions
-                    .entry(nt.clone())
+                    .entry(*nt)
                     .or_default()
                     .push(new_prod);
             }
@@ -652,3 +710,9 @@ impl CFG {
         inverted_cfg
     }
 }
+
+// Нетерминал Aε префиксной грамматики для нетерминала A
+fn get_epsilon_version(symbols: &mut Alphabet, symbol: Symbol) -> Symbol {
+    let name = format!("{}ε", symbols.get_token(symbol));
+    symbols.intern(&name)
+}
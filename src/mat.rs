@@ -1,92 +1,361 @@
 #![allow(dead_code)]
 
-use std::process::Command;
-
-use once_cell::sync::Lazy;
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
 
+use crate::alphabet::{Alphabet, Symbol, Word};
 use crate::automata::Automata;
+use crate::mat::conformance_testing::{check_equivalence_conformance, ConformanceTesting};
+use crate::mat::enumerator::WordEnumerator;
+use crate::mat::oracle_process::{run_oracle, OracleProcess};
+use crate::mat::random_testing::{check_equivalence_random, RandomTesting};
 
-static mut LAST_TEST_WORD: usize = 0;
-static mut TEST_WORDS: Lazy<Vec<String>> = Lazy::new(|| vec!["".to_string()]);
+pub mod automata_mat;
+pub mod caching_mat;
+pub mod conformance_testing;
+pub mod counting_mat;
+pub mod enumerator;
+pub mod oracle_process;
+pub mod random_testing;
 
-// TODO: исправить, поместив генератор в Mat
-fn get_next_word(mat: &dyn Mat) -> String {
-    unsafe {
-        let next_word = TEST_WORDS[LAST_TEST_WORD].to_owned();
-        LAST_TEST_WORD += 1;
+// Missing -- слово языка, отвергнутое гипотезой,
+// Extra -- слово, принятое гипотезой, но не принадлежащее языку
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CounterexampleKind {
+    Missing,
+    Extra,
+}
 
-        if LAST_TEST_WORD == TEST_WORDS.len() {
-            let alphabet = mat.get_alphabet();
-            let mut extension = Vec::with_capacity(TEST_WORDS.len() * alphabet.len());
+pub enum EquivalenceCheckResult {
+    Ok,
+    Counterexample(Word, CounterexampleKind),
+}
 
-            for word in TEST_WORDS.iter() {
-                for letter in alphabet.chars() {
-                    extension.push(format!("{word}{letter}"));
-                }
+#[derive(Clone, Copy, Debug)]
+pub enum EquivalenceMode {
+    Exhaustive,
+    Random(RandomTesting),
+    Conformance(ConformanceTesting),
+}
+
+#[derive(Debug)]
+pub enum MatError {
+    Spawn(io::Error),
+    Io(io::Error),
+    Timeout(Duration),
+    MalformedAnswer(String),
+    NonZeroExit(Option<i32>),
+    // Ответы оракула не позволяют обучению продвинуться, например противоречат друг другу
+    NoProgress(String),
+}
+
+impl fmt::Display for MatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Spawn(error) => write!(f, "failed to start oracle: {error}"),
+            Self::Io(error) => write!(f, "oracle I/O error: {error}"),
+            Self::Timeout(timeout) => write!(f, "oracle did not answer within {timeout:?}"),
+            Self::MalformedAnswer(answer) => {
+                write!(f, "oracle answered '{answer}' instead of 0 or 1")
             }
+            Self::NonZeroExit(Some(code)) => write!(f, "oracle exited with code {code}"),
+            Self::NonZeroExit(None) => write!(f, "oracle was terminated by a signal"),
+            Self::NoProgress(reason) => write!(f, "learning made no progress: {reason}"),
+        }
+    }
+}
 
-            TEST_WORDS.clear();
-            TEST_WORDS.append(&mut extension);
-            LAST_TEST_WORD = 0;
+// attempts -- общее число попыток запроса, delay -- пауза между ними
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub attempts: usize,
+    pub delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 1,
+            delay: Duration::ZERO,
         }
+    }
+}
 
-        next_word
+// Запросы могут выполняться из нескольких потоков одновременно
+pub trait Mat: Sync {
+    fn check_membership(&self, word: &[Symbol]) -> Result<bool, MatError>;
+
+    fn check_membership_batch(&self, words: &[Word]) -> Result<Vec<bool>, MatError> {
+        words
+            .iter()
+            .map(|word| self.check_membership(word))
+            .collect()
     }
+
+    fn check_equivalence(
+        &self,
+        automata: &dyn Automata,
+    ) -> Result<EquivalenceCheckResult, MatError>;
+
+    // Проверка эквивалентности, тестовые запросы принадлежности которой отправляются
+    // в membership, -- так их видят декораторы, например кеш
+    fn check_equivalence_using(
+        &self,
+        _membership: &dyn Mat,
+        automata: &dyn Automata,
+    ) -> Result<EquivalenceCheckResult, MatError> {
+        self.check_equivalence(automata)
+    }
+
+    fn get_alphabet(&self) -> &Alphabet;
 }
 
-pub enum EquivalenceCheckResult {
-    Ok,
-    Counterexample(String),
+// Слова распределяются между workers потоками, ответы возвращаются в исходном порядке.
+// При нескольких ошибках возвращается ошибка первого по порядку слова
+pub fn check_membership_parallel(
+    mat: &dyn Mat,
+    words: &[Word],
+    workers: usize,
+) -> Result<Vec<bool>, MatError> {
+    if workers <= 1 || words.len() <= 1 {
+        return words
+            .iter()
+            .map(|word| mat.check_membership(word))
+            .collect();
+    }
+
+    let next_index = AtomicUsize::new(0);
+    let is_failed = AtomicBool::new(false);
+
+    let mut results = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers.min(words.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::<(usize, Result<bool, MatError>)>::new();
+                    while !is_failed.load(Ordering::Relaxed) {
+                        let index = next_index.fetch_add(1, Ordering::Relaxed);
+                        if index >= words.len() {
+                            break;
+                        }
+
+                        let result = mat.check_membership(&words[index]);
+                        if result.is_err() {
+                            is_failed.store(true, Ordering::Relaxed);
+                        }
+                        results.push((index, result));
+                    }
+                    results
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    results.sort_by_key(|(index, _)| *index);
+
+    results.into_iter().map(|(_, result)| result).collect()
 }
 
-pub trait Mat {
-    fn check_membership(&self, word: &str) -> bool;
+// Перебор слов в порядке возрастания длины
+pub fn check_equivalence_exhaustive(
+    mat: &dyn Mat,
+    automata: &dyn Automata,
+    words: &mut dyn Iterator<Item = Word>,
+    max_tests: usize,
+    word_max_len: usize,
+) -> Result<EquivalenceCheckResult, MatError> {
+    for word in words.take(max_tests) {
+        if word.len().ge(&word_max_len) {
+            return Ok(EquivalenceCheckResult::Ok);
+        }
 
-    fn check_equivalence(&self, automata: &dyn Automata) -> EquivalenceCheckResult;
+        if let Some(kind) = get_counterexample_kind(mat, automata, &word)? {
+            return Ok(EquivalenceCheckResult::Counterexample(word, kind));
+        }
+    }
 
-    fn get_alphabet(&self) -> String;
+    Ok(EquivalenceCheckResult::Ok)
+}
+
+pub fn check_equivalence_with_mode(
+    mat: &dyn Mat,
+    automata: &dyn Automata,
+    mode: &EquivalenceMode,
+    words: &mut dyn Iterator<Item = Word>,
+    max_tests: usize,
+    word_max_len: usize,
+) -> Result<EquivalenceCheckResult, MatError> {
+    match mode {
+        EquivalenceMode::Exhaustive => {
+            check_equivalence_exhaustive(mat, automata, words, max_tests, word_max_len)
+        }
+        EquivalenceMode::Random(testing) => check_equivalence_random(mat, automata, testing),
+        EquivalenceMode::Conformance(testing) => {
+            check_equivalence_conformance(mat, automata, testing)
+        }
+    }
+}
+
+pub fn get_counterexample_kind(
+    mat: &dyn Mat,
+    automata: &dyn Automata,
+    word: &[Symbol],
+) -> Result<Option<CounterexampleKind>, MatError> {
+    let kind = match (mat.check_membership(word)?, automata.check_membership(word)) {
+        (true, false) => Some(CounterexampleKind::Missing),
+        (false, true) => Some(CounterexampleKind::Extra),
+        _ => None,
+    };
+
+    Ok(kind)
 }
 
 pub struct MatScript {
-    alphabet: String,
+    alphabet: Alphabet,
     oracle_path: String,
     max_tests: usize,
     word_max_len: usize,
+    equivalence_mode: EquivalenceMode,
+    enumerator: Mutex<WordEnumerator>,
+    // В пакетном режиме процесс оракула запускается при первом запросе
+    // и перезапускается после ошибки
+    batch_process: Option<Mutex<Option<OracleProcess>>>,
+    timeout: Option<Duration>,
+    retry_policy: RetryPolicy,
+    // Число одновременно запущенных процессов оракула при пакетных запросах
+    workers: usize,
 }
 
 impl Mat for MatScript {
-    fn check_membership(&self, word: &str) -> bool {
-        let output = Command::new(&self.oracle_path).arg(word).output().unwrap();
-        String::from_utf8(output.stdout).unwrap().eq("1\n")
+    fn check_membership(&self, word: &[Symbol]) -> Result<bool, MatError> {
+        if self.batch_process.is_some() {
+            return Ok(self.check_membership_batch(&[word.to_vec()])?[0]);
+        }
+
+        let word = self.alphabet.format_word(word);
+        self.retry(|| run_oracle(&self.oracle_path, &word, self.timeout))
     }
 
-    fn check_equivalence(&self, automata: &dyn Automata) -> EquivalenceCheckResult {
-        for _ in 0..self.max_tests {
-            let word = get_next_word(self);
-            if word.len().ge(&self.word_max_len) {
-                return EquivalenceCheckResult::Ok;
+    fn check_membership_batch(&self, words: &[Word]) -> Result<Vec<bool>, MatError> {
+        let Some(batch_process) = &self.batch_process else {
+            return check_membership_parallel(self, words, self.workers);
+        };
+
+        let words: Vec<String> = words
+            .iter()
+            .map(|word| self.alphabet.format_word(word))
+            .collect();
+
+        self.retry(|| {
+            let mut process = batch_process.lock().unwrap();
+            if process.is_none() {
+                *process = Some(OracleProcess::spawn(&self.oracle_path)?);
             }
 
-            if self.check_membership(&word) && !automata.check_membership(&word) {
-                return EquivalenceCheckResult::Counterexample(word);
+            let answers = process.as_mut().unwrap().query(&words, self.timeout);
+            if answers.is_err() {
+                *process = None;
             }
-        }
 
-        EquivalenceCheckResult::Ok
+            answers
+        })
+    }
+
+    fn check_equivalence(
+        &self,
+        automata: &dyn Automata,
+    ) -> Result<EquivalenceCheckResult, MatError> {
+        self.check_equivalence_using(self, automata)
     }
 
-    fn get_alphabet(&self) -> String {
-        self.alphabet.to_string()
+    fn check_equivalence_using(
+        &self,
+        membership: &dyn Mat,
+        automata: &dyn Automata,
+    ) -> Result<EquivalenceCheckResult, MatError> {
+        // Каждая проверка начинает перебор заново и не зависит от предыдущих
+        let mut enumerator = self.enumerator.lock().unwrap();
+        enumerator.reset();
+
+        check_equivalence_with_mode(
+            membership,
+            automata,
+            &self.equivalence_mode,
+            &mut *enumerator,
+            self.max_tests,
+            self.word_max_len,
+        )
+    }
+
+    fn get_alphabet(&self) -> &Alphabet {
+        &self.alphabet
     }
 }
 
 impl MatScript {
-    pub fn new(alphabet: &str, oracle_path: &str, max_tests: usize, word_max_len: usize) -> Self {
+    pub fn new(
+        alphabet: Alphabet,
+        oracle_path: &str,
+        max_tests: usize,
+        word_max_len: usize,
+    ) -> Self {
         Self {
-            alphabet: alphabet.to_owned(),
+            enumerator: Mutex::new(WordEnumerator::new(alphabet.len())),
+            alphabet,
             oracle_path: oracle_path.to_owned(),
             max_tests,
             word_max_len,
+            equivalence_mode: EquivalenceMode::Exhaustive,
+            batch_process: None,
+            timeout: None,
+            retry_policy: RetryPolicy::default(),
+            workers: 1,
+        }
+    }
+
+    pub fn with_equivalence_mode(mut self, equivalence_mode: EquivalenceMode) -> Self {
+        self.equivalence_mode = equivalence_mode;
+        self
+    }
+
+    pub fn with_batch_mode(mut self) -> Self {
+        self.batch_process = Some(Mutex::new(None));
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    // Не действует в пакетном режиме, где оракул запущен в одном экземпляре
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    fn retry<T>(&self, query: impl Fn() -> Result<T, MatError>) -> Result<T, MatError> {
+        let mut attempt = 1;
+        loop {
+            match query() {
+                Err(_) if attempt < self.retry_policy.attempts => {
+                    attempt += 1;
+                    thread::sleep(self.retry_policy.delay);
+                }
+                result => return result,
+            }
         }
     }
 }
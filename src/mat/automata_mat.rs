@@ -0,0 +1,39 @@
+use crate::alphabet::{Alphabet, Symbol};
+use crate::automata::Automata;
+
+use super::{CounterexampleKind, EquivalenceCheckResult, Mat, MatError};
+
+// Точный оракул, заданный эталонным автоматом
+pub struct AutomataMat<'a> {
+    pub automata: &'a (dyn Automata + Sync),
+    pub alphabet: Alphabet,
+}
+
+impl<'a> Mat for AutomataMat<'a> {
+    fn check_membership(&self, word: &[Symbol]) -> Result<bool, MatError> {
+        Ok(self.automata.check_membership(word))
+    }
+
+    fn check_equivalence(
+        &self,
+        automata: &dyn Automata,
+    ) -> Result<EquivalenceCheckResult, MatError> {
+        let result = match self.automata.equivalent(automata) {
+            Some(word) => {
+                let kind = if self.automata.check_membership(&word) {
+                    CounterexampleKind::Missing
+                } else {
+                    CounterexampleKind::Extra
+                };
+                EquivalenceCheckResult::Counterexample(word, kind)
+            }
+            None => EquivalenceCheckResult::Ok,
+        };
+
+        Ok(result)
+    }
+
+    fn get_alphabet(&self) -> &Alphabet {
+        &self.alphabet
+    }
+}
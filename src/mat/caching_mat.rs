@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::alphabet::{Alphabet, Symbol, Word};
+use crate::automata::Automata;
+
+use super::{EquivalenceCheckResult, Mat, MatError};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStatistics {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+// Запоминает ответы на запросы принадлежности. Хранилище -- текстовый файл,
+// в который дописываются строки вида `<0|1>\t<слово>`
+pub struct CachingMat<'a> {
+    mat: &'a dyn Mat,
+    cache: Mutex<HashMap<Word, bool>>,
+    store: Option<Mutex<File>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl<'a> Mat for CachingMat<'a> {
+    fn check_membership(&self, word: &[Symbol]) -> Result<bool, MatError> {
+        if let Some(is_member) = self.cache.lock().unwrap().get(word) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(*is_member);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        // Ошибки оракула не запоминаются
+        let is_member = self.mat.check_membership(word)?;
+        self.remember(word, is_member)?;
+
+        Ok(is_member)
+    }
+
+    // Оракулу пакетом передаются только слова, ответов на которые нет в кеше
+    fn check_membership_batch(&self, words: &[Word]) -> Result<Vec<bool>, MatError> {
+        let mut answers = Vec::<Option<bool>>::with_capacity(words.len());
+        let mut unknown_words = Vec::<Word>::new();
+        {
+            let cache = self.cache.lock().unwrap();
+            for word in words {
+                let answer = cache.get(word).copied();
+                if answer.is_none() {
+                    unknown_words.push(word.to_owned());
+                }
+                answers.push(answer);
+            }
+        }
+        self.hits
+            .fetch_add(words.len() - unknown_words.len(), Ordering::Relaxed);
+        self.misses
+            .fetch_add(unknown_words.len(), Ordering::Relaxed);
+
+        let unknown_answers = self.mat.check_membership_batch(&unknown_words)?;
+        for (word, is_member) in unknown_words.iter().zip(&unknown_answers) {
+            self.remember(word, *is_member)?;
+        }
+
+        let mut unknown_answers = unknown_answers.into_iter();
+        Ok(answers
+            .into_iter()
+            .map(|answer| answer.unwrap_or_else(|| unknown_answers.next().unwrap()))
+            .collect())
+    }
+
+    // Запросы эквивалентности не кешируются, но тестовые запросы принадлежности
+    // исходного оракула проходят через кеш
+    fn check_equivalence(
+        &self,
+        automata: &dyn Automata,
+    ) -> Result<EquivalenceCheckResult, MatError> {
+        self.mat.check_equivalence_using(self, automata)
+    }
+
+    fn check_equivalence_using(
+        &self,
+        membership: &dyn Mat,
+        automata: &dyn Automata,
+    ) -> Result<EquivalenceCheckResult, MatError> {
+        self.mat.check_equivalence_using(membership, automata)
+    }
+
+    fn get_alphabet(&self) -> &Alphabet {
+        self.mat.get_alphabet()
+    }
+}
+
+impl<'a> CachingMat<'a> {
+    pub fn new(mat: &'a dyn Mat) -> Self {
+        Self {
+            mat,
+            cache: Mutex::new(HashMap::new()),
+            store: None,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    // Загружает ответы, сохранённые предыдущими запусками, и дописывает новые в тот же файл
+    pub fn with_store(mat: &'a dyn Mat, path: &str) -> io::Result<Self> {
+        let mut caching_mat = Self::new(mat);
+
+        if let Ok(file) = File::open(path) {
+            let cache = caching_mat.cache.get_mut().unwrap();
+            for (index, line) in BufReader::new(file).lines().enumerate() {
+                let line = line?;
+                if line.is_empty() {
+                    continue;
+                }
+
+                let entry = line.split_once('\t').and_then(|(answer, word)| {
+                    let is_member = match answer {
+                        "0" => false,
+                        "1" => true,
+                        _ => return None,
+                    };
+                    Some((mat.get_alphabet().parse_word(word)?, is_member))
+                });
+                let Some((word, is_member)) = entry else {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {}: malformed cache entry '{line}'", index + 1),
+                    ));
+                };
+                cache.insert(word, is_member);
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        caching_mat.store = Some(Mutex::new(file));
+
+        Ok(caching_mat)
+    }
+
+    fn remember(&self, word: &[Symbol], is_member: bool) -> Result<(), MatError> {
+        self.cache.lock().unwrap().insert(word.to_vec(), is_member);
+
+        if let Some(store) = &self.store {
+            let line = format!(
+                "{}\t{}",
+                is_member as u8,
+                self.mat.get_alphabet().format_word(word)
+            );
+            writeln!(store.lock().unwrap(), "{line}").map_err(MatError::Io)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn get_statistics(&self) -> CacheStatistics {
+        CacheStatistics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
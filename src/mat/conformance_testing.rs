@@ -0,0 +1,31 @@
+use crate::automata::{conformance::ConformanceMethod, Automata, AutomataImpl};
+
+use super::{get_counterexample_kind, EquivalenceCheckResult, Mat, MatError};
+
+// extra_states -- на сколько состояний минимальный автомат языка
+// может превосходить гипотезу, чтобы расхождение гарантированно нашлось
+#[derive(Clone, Copy, Debug)]
+pub struct ConformanceTesting {
+    pub method: ConformanceMethod,
+    pub extra_states: usize,
+}
+
+pub fn check_equivalence_conformance(
+    mat: &dyn Mat,
+    automata: &dyn Automata,
+    testing: &ConformanceTesting,
+) -> Result<EquivalenceCheckResult, MatError> {
+    let alphabet = mat.get_alphabet();
+
+    let hypothesis = automata.minimize();
+    let hypothesis = hypothesis.as_any().downcast_ref::<AutomataImpl>().unwrap();
+
+    // Тесты упорядочены по длине, поэтому находится кратчайший контрпример набора
+    for word in hypothesis.get_conformance_suite(alphabet, testing.method, testing.extra_states) {
+        if let Some(kind) = get_counterexample_kind(mat, automata, &word)? {
+            return Ok(EquivalenceCheckResult::Counterexample(word, kind));
+        }
+    }
+
+    Ok(EquivalenceCheckResult::Ok)
+}
@@ -0,0 +1,83 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::alphabet::{Alphabet, Symbol, Word};
+use crate::automata::Automata;
+
+use super::{EquivalenceCheckResult, Mat, MatError};
+
+// Повторные запросы принадлежности -- те, на которые ответил бы кеш
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueryStatistics {
+    pub membership_queries: usize,
+    pub unique_membership_queries: usize,
+    pub equivalence_queries: usize,
+}
+
+// Считает запросы, проходящие к оракулу
+pub struct CountingMat<'a> {
+    mat: &'a dyn Mat,
+    membership_queries: AtomicUsize,
+    equivalence_queries: AtomicUsize,
+    words: Mutex<HashSet<Word>>,
+}
+
+impl<'a> Mat for CountingMat<'a> {
+    fn check_membership(&self, word: &[Symbol]) -> Result<bool, MatError> {
+        self.count_words(&[word.to_vec()]);
+        self.mat.check_membership(word)
+    }
+
+    fn check_membership_batch(&self, words: &[Word]) -> Result<Vec<bool>, MatError> {
+        self.count_words(words);
+        self.mat.check_membership_batch(words)
+    }
+
+    // Запросы принадлежности, сделанные при проверке эквивалентности, не учитываются
+    fn check_equivalence(
+        &self,
+        automata: &dyn Automata,
+    ) -> Result<EquivalenceCheckResult, MatError> {
+        self.equivalence_queries.fetch_add(1, Ordering::Relaxed);
+        self.mat.check_equivalence(automata)
+    }
+
+    fn check_equivalence_using(
+        &self,
+        membership: &dyn Mat,
+        automata: &dyn Automata,
+    ) -> Result<EquivalenceCheckResult, MatError> {
+        self.equivalence_queries.fetch_add(1, Ordering::Relaxed);
+        self.mat.check_equivalence_using(membership, automata)
+    }
+
+    fn get_alphabet(&self) -> &Alphabet {
+        self.mat.get_alphabet()
+    }
+}
+
+impl<'a> CountingMat<'a> {
+    pub fn new(mat: &'a dyn Mat) -> Self {
+        Self {
+            mat,
+            membership_queries: AtomicUsize::new(0),
+            equivalence_queries: AtomicUsize::new(0),
+            words: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn get_statistics(&self) -> QueryStatistics {
+        QueryStatistics {
+            membership_queries: self.membership_queries.load(Ordering::Relaxed),
+            unique_membership_queries: self.words.lock().unwrap().len(),
+            equivalence_queries: self.equivalence_queries.load(Ordering::Relaxed),
+        }
+    }
+
+    fn count_words(&self, words: &[Word]) {
+        self.membership_queries
+            .fetch_add(words.len(), Ordering::Relaxed);
+        self.words.lock().unwrap().extend(words.iter().cloned());
+    }
+}
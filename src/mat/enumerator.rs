@@ -0,0 +1,52 @@
+use crate::alphabet::{Symbol, Word};
+
+// Перебор слов над алфавитом в порядке возрастания длины,
+// слова одной длины -- в лексикографическом порядке символов
+#[derive(Clone, Debug)]
+pub struct WordEnumerator {
+    alphabet_size: usize,
+    next_word: Option<Word>,
+}
+
+impl WordEnumerator {
+    pub fn new(alphabet_size: usize) -> Self {
+        Self {
+            alphabet_size,
+            next_word: Some(Word::new()),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.next_word = Some(Word::new());
+    }
+
+    fn get_successor(&self, word: &[Symbol]) -> Option<Word> {
+        if self.alphabet_size == 0 {
+            return None;
+        }
+
+        let mut successor = word.to_vec();
+        for symbol in successor.iter_mut().rev() {
+            if *symbol + 1 < self.alphabet_size {
+                *symbol += 1;
+                return Some(successor);
+            }
+            *symbol = 0;
+        }
+
+        // Все слова текущей длины перебраны
+        successor.push(0);
+        Some(successor)
+    }
+}
+
+impl Iterator for WordEnumerator {
+    type Item = Word;
+
+    fn next(&mut self) -> Option<Word> {
+        let word = self.next_word.take()?;
+        self.next_word = self.get_successor(&word);
+
+        Some(word)
+    }
+}
@@ -0,0 +1,166 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::MatError;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+// Однократный запуск оракула со словом в качестве аргумента
+pub fn run_oracle(
+    oracle_path: &str,
+    word: &str,
+    timeout: Option<Duration>,
+) -> Result<bool, MatError> {
+    let mut child = Command::new(oracle_path)
+        .arg(word)
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(MatError::Spawn)?;
+
+    // Вывод читается параллельно, чтобы оракул не заблокировался на заполненном канале
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(MatError::Io)? {
+            break status;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(MatError::Timeout(timeout.unwrap()));
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let output = reader.join().unwrap().map_err(MatError::Io)?;
+    if !status.success() {
+        return Err(MatError::NonZeroExit(status.code()));
+    }
+
+    parse_answer(&String::from_utf8_lossy(&output))
+}
+
+fn parse_answer(answer: &str) -> Result<bool, MatError> {
+    match answer.trim_end() {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        answer => Err(MatError::MalformedAnswer(answer.to_owned())),
+    }
+}
+
+// Долгоживущий процесс оракула: запускается без аргументов, читает слова
+// со стандартного входа по одному в строке и отвечает строками `0` или `1`.
+// Ввод и вывод обслуживаются отдельными потоками, поэтому большой пакет
+// не блокирует оракул на переполненном канале, а ожидание ответа
+// можно прервать по таймауту
+pub struct OracleProcess {
+    child: Child,
+    words: Option<mpsc::Sender<Vec<String>>>,
+    answers: mpsc::Receiver<io::Result<String>>,
+}
+
+impl OracleProcess {
+    pub fn spawn(oracle_path: &str) -> Result<Self, MatError> {
+        let mut child = Command::new(oracle_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(MatError::Spawn)?;
+
+        let mut stdin = child.stdin.take().unwrap();
+        let (words_sender, words_receiver) = mpsc::channel::<Vec<String>>();
+        thread::spawn(move || -> io::Result<()> {
+            for words in words_receiver {
+                for word in words {
+                    writeln!(stdin, "{word}")?;
+                }
+                stdin.flush()?;
+            }
+            Ok(())
+        });
+
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let (answers_sender, answers_receiver) = mpsc::channel::<io::Result<String>>();
+        thread::spawn(move || loop {
+            let mut line = String::new();
+            let result = stdout.read_line(&mut line).map(|_| line);
+            let is_last = !matches!(&result, Ok(line) if !line.is_empty());
+            if answers_sender.send(result).is_err() || is_last {
+                break;
+            }
+        });
+
+        Ok(Self {
+            child,
+            words: Some(words_sender),
+            answers: answers_receiver,
+        })
+    }
+
+    // После ошибки процесс завершается и больше не может отвечать на запросы
+    pub fn query(
+        &mut self,
+        words: &[String],
+        timeout: Option<Duration>,
+    ) -> Result<Vec<bool>, MatError> {
+        // Если оракул уже закрыл вход, ошибка обнаружится при чтении ответов
+        let _ = self.words.as_ref().unwrap().send(words.to_vec());
+
+        let answers = self.receive_answers(words.len(), timeout);
+        if answers.is_err() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+
+        answers
+    }
+
+    fn receive_answers(
+        &mut self,
+        count: usize,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<bool>, MatError> {
+        let mut answers = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let line = match timeout {
+                Some(timeout) => match self.answers.recv_timeout(timeout) {
+                    Ok(line) => line,
+                    Err(mpsc::RecvTimeoutError::Timeout) => return Err(MatError::Timeout(timeout)),
+                    Err(mpsc::RecvTimeoutError::Disconnected) => Ok(String::new()),
+                },
+                None => self.answers.recv().unwrap_or_else(|_| Ok(String::new())),
+            };
+            let line = line.map_err(MatError::Io)?;
+
+            // Конец вывода -- оракул завершился, не ответив на все запросы
+            if line.is_empty() {
+                let status = self.child.wait().map_err(MatError::Io)?;
+                if !status.success() {
+                    return Err(MatError::NonZeroExit(status.code()));
+                }
+                return Err(MatError::MalformedAnswer(String::new()));
+            }
+
+            answers.push(parse_answer(&line)?);
+        }
+
+        Ok(answers)
+    }
+}
+
+impl Drop for OracleProcess {
+    fn drop(&mut self) {
+        // Закрытие входа сообщает оракулу о конце запросов
+        self.words.take();
+        let _ = self.child.wait();
+    }
+}
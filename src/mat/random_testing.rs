@@ -0,0 +1,49 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::automata::{str_generator::StringGenerator, Automata, AutomataImpl};
+
+use super::{get_counterexample_kind, EquivalenceCheckResult, Mat, MatError};
+
+// Параметры случайного тестирования: samples слов генерируется из гипотезы
+// и столько же из её дополнения, каждое слово искажается mutations раз
+#[derive(Clone, Copy, Debug)]
+pub struct RandomTesting {
+    pub samples: usize,
+    pub mutations: usize,
+    pub seed: u64,
+}
+
+// Слова выбираются случайными путями по минимальной гипотезе и её дополнению,
+// поэтому проверяются и длинные слова, недоступные полному перебору
+pub fn check_equivalence_random(
+    mat: &dyn Mat,
+    automata: &dyn Automata,
+    testing: &RandomTesting,
+) -> Result<EquivalenceCheckResult, MatError> {
+    let alphabet = mat.get_alphabet();
+    let mut rng = StdRng::seed_from_u64(testing.seed);
+
+    // В минимальных автоматах нет тупиковых состояний, и случайный путь всегда завершается
+    let hypothesis = automata.minimize();
+    let complement = hypothesis.get_complement(alphabet).minimize();
+
+    for source in [hypothesis.as_ref(), complement.as_ref()] {
+        let source = source.as_any().downcast_ref::<AutomataImpl>().unwrap();
+        let mut generator = StringGenerator::from_automata_seeded(source, rng.gen());
+
+        for word in generator.gen_strs(testing.samples) {
+            let mut words = vec![word.clone()];
+            for _ in 0..testing.mutations {
+                words.push(generator.mutate_word(&word, alphabet));
+            }
+
+            for word in words {
+                if let Some(kind) = get_counterexample_kind(mat, automata, &word)? {
+                    return Ok(EquivalenceCheckResult::Counterexample(word, kind));
+                }
+            }
+        }
+    }
+
+    Ok(EquivalenceCheckResult::Ok)
+}
@@ -1,91 +1,335 @@
 #![allow(dead_code)]
 
 mod extended_table;
+pub mod hypothesis;
+pub mod kearns_vazirani;
+pub mod lstar;
 mod main_table;
+pub mod observer;
+pub mod report;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
 
-use crate::automata::{Automata, AutomataImpl, START};
+use crate::alphabet::{Symbol, Word};
+use crate::automata::{conformance::AccessWords, Automata, AutomataImpl, START};
 use crate::config::EPSILON;
-use crate::mat::{EquivalenceCheckResult, Mat};
+use crate::mat::{counting_mat::CountingMat, EquivalenceCheckResult, Mat, MatError};
 use crate::nl::extended_table::ExtendedTable;
+use crate::nl::hypothesis::{Hypothesis, HypothesisState};
 use crate::nl::main_table::{CoverageMode, MainTable};
+use crate::nl::observer::NlObserver;
+use crate::nl::report::{LearningReport, TableSizes};
 
 // TODO: оптимизировать итерации в check_consistency
 // TODO: использовать BTreeSet заместо HashSet?
 
+// Гипотеза возвращается как есть, детерминизация -- по запросу
 pub trait Nl {
-    fn get_dfa(&mut self) -> Box<dyn Automata>;
+    fn get_hypothesis(&mut self) -> Result<(Hypothesis, LearningReport), MatError>;
+
+    fn get_dfa(&mut self) -> Result<(Box<dyn Automata>, LearningReport), MatError> {
+        let (hypothesis, report) = self.get_hypothesis()?;
+        Ok((hypothesis.determinize(), report))
+    }
+}
+
+// AllPrefixes -- все префиксы контрпримера добавляются в таблицу как строки,
+// AllSuffixes -- все суффиксы добавляются как столбцы (Maler–Pnueli),
+// BinarySearch -- добавляется один различающий суффикс (Rivest–Schapire)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CounterexampleStrategy {
+    AllPrefixes,
+    AllSuffixes,
+    BinarySearch,
 }
 
 pub struct NlImpl<'a> {
-    mat: &'a dyn Mat,
-    main_table: MainTable<'a>,
-    extended_table: ExtendedTable<'a>,
+    mat: CountingMat<'a>,
+    main_table: MainTable,
+    extended_table: ExtendedTable,
+    report: LearningReport,
+    counterexample_strategy: CounterexampleStrategy,
+    observers: Vec<Box<dyn NlObserver + 'a>>,
 }
 
 impl<'a> Nl for NlImpl<'a> {
-    fn get_dfa(&mut self) -> Box<dyn Automata> {
-        loop {
-            if let CompletenessCheckResult::UncoveredPrefix(prefix) = self.check_completeness() {
-                self.insert_prefix(&prefix);
+    fn get_hypothesis(&mut self) -> Result<(Hypothesis, LearningReport), MatError> {
+        let start = Instant::now();
+
+        // Начальные ε-строка и ε-столбец добавляются здесь, а не в конструкторе,
+        // чтобы о них узнали наблюдатели
+        self.insert_prefix(EPSILON)?;
+        self.insert_suffix(EPSILON)?;
+
+        let nfa = loop {
+            let timer = Instant::now();
+            let is_fixed = self.fix_completeness()?;
+            self.report.time.completeness += timer.elapsed();
+            if is_fixed {
                 continue;
             }
 
-            if let ConsistencyCheckResult::DistinguishingSuffix(suffix) = self.check_consistency() {
-                self.insert_suffix(&suffix);
+            let timer = Instant::now();
+            let is_fixed = self.fix_consistency()?;
+            self.report.time.consistency += timer.elapsed();
+            if is_fixed {
                 continue;
             }
 
+            let timer = Instant::now();
             let nfa = self.build_nfa();
-            let dfa = nfa.determinize();
+            self.report.time.hypothesis += timer.elapsed();
+            for observer in &mut self.observers {
+                observer.on_hypothesis_built(nfa.as_ref());
+            }
 
-            if let EquivalenceCheckResult::Counterexample(word) =
-                self.mat.check_equivalence(dfa.as_ref())
-            {
-                self.insert_prefix_recursive(&word);
+            // Учитель проверяет эквивалентность по самому NFA
+            let timer = Instant::now();
+            let result = self.mat.check_equivalence(nfa.as_ref())?;
+            self.report.time.equivalence += timer.elapsed();
+
+            // Оба вида контрпримеров обрабатываются одинаково
+            if let EquivalenceCheckResult::Counterexample(word, kind) = result {
+                for observer in &mut self.observers {
+                    observer.on_counterexample(&word, kind);
+                }
+
+                let timer = Instant::now();
+                self.process_counterexample(&word, nfa.as_ref())?;
+                self.report.counterexamples += 1;
+                self.report.time.counterexamples += timer.elapsed();
                 continue;
             }
 
-            break dfa;
-        }
+            break nfa;
+        };
+
+        let hypothesis = Hypothesis {
+            automata: nfa,
+            states: self.get_hypothesis_states(),
+        };
+
+        self.report.time.total += start.elapsed();
+        Ok((hypothesis, self.get_report()))
     }
 }
 
 enum CompletenessCheckResult {
     Ok,
-    UncoveredPrefix(String),
+    UncoveredPrefix(Word),
 }
 
 enum ConsistencyCheckResult {
     Ok,
-    DistinguishingSuffix(String),
+    DistinguishingSuffix(Word),
+}
+
+// Ответы на все пары префикс-суффикс, построчно, одним пакетом. Таблицы
+// изменяются только после того, как получены все ответы, поэтому ошибка оракула
+// оставляет их в прежнем состоянии
+fn query_table(mat: &dyn Mat, prefixes: &[Word], suffixes: &[Word]) -> Result<Vec<bool>, MatError> {
+    let words: Vec<Word> = prefixes
+        .iter()
+        .flat_map(|prefix| {
+            suffixes
+                .iter()
+                .map(move |suffix| [prefix.as_slice(), suffix].concat())
+        })
+        .collect();
+    mat.check_membership_batch(&words)
 }
 
 impl<'a> NlImpl<'a> {
     pub fn new(mat: &'a dyn Mat) -> Self {
         Self {
-            mat,
-            main_table: MainTable::new(mat),
-            extended_table: ExtendedTable::new(mat),
+            mat: CountingMat::new(mat),
+            main_table: MainTable::new(),
+            extended_table: ExtendedTable::new(),
+            report: LearningReport::default(),
+            counterexample_strategy: CounterexampleStrategy::BinarySearch,
+            observers: Vec::new(),
+        }
+    }
+
+    pub fn with_counterexample_strategy(mut self, strategy: CounterexampleStrategy) -> Self {
+        self.counterexample_strategy = strategy;
+        self
+    }
+
+    pub fn with_observer(mut self, observer: Box<dyn NlObserver + 'a>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    // Отчёт доступен и после прерванного ошибкой обучения
+    pub fn get_report(&self) -> LearningReport {
+        LearningReport {
+            queries: self.mat.get_statistics(),
+            table: TableSizes {
+                prefixes: self.main_table.prefixes.len(),
+                basic_prefixes: self.main_table.basic_prefixes.len(),
+                suffixes: self.main_table.suffixes.len(),
+                extended_prefixes: self.extended_table.prefixes.len(),
+            },
+            ..self.report.clone()
         }
     }
 
-    fn insert_prefix_recursive(&mut self, prefix: &str) {
+    // Возвращают true, если таблица была дополнена. Если исправление не добавило
+    // в таблицу ничего нового, то же нарушение нашлось бы снова, поэтому это ошибка
+    fn fix_completeness(&mut self) -> Result<bool, MatError> {
+        let CompletenessCheckResult::UncoveredPrefix(prefix) = self.check_completeness() else {
+            return Ok(false);
+        };
+
+        for observer in &mut self.observers {
+            observer.on_uncovered_prefix(&prefix);
+        }
+        if !self.insert_prefix(&prefix)? {
+            return Err(MatError::NoProgress(format!(
+                "uncovered prefix '{}' is already in the table",
+                self.mat.get_alphabet().format_word(&prefix)
+            )));
+        }
+        self.report.completeness_fixes += 1;
+        Ok(true)
+    }
+
+    fn fix_consistency(&mut self) -> Result<bool, MatError> {
+        let ConsistencyCheckResult::DistinguishingSuffix(suffix) = self.check_consistency() else {
+            return Ok(false);
+        };
+
+        for observer in &mut self.observers {
+            observer.on_distinguishing_suffix(&suffix);
+        }
+        if !self.insert_suffix(&suffix)? {
+            return Err(MatError::NoProgress(format!(
+                "distinguishing suffix '{}' is already in the table",
+                self.mat.get_alphabet().format_word(&suffix)
+            )));
+        }
+        self.report.consistency_fixes += 1;
+        Ok(true)
+    }
+
+    // Если суффиксы контрпримера уже есть в таблице, добавляются его префиксы.
+    // Контрпример, все префиксы и суффиксы которого уже есть в таблице, её не изменяет
+    fn process_counterexample(
+        &mut self,
+        word: &[Symbol],
+        nfa: &dyn Automata,
+    ) -> Result<(), MatError> {
+        let is_changed = match self.counterexample_strategy {
+            CounterexampleStrategy::AllPrefixes => false,
+            CounterexampleStrategy::AllSuffixes => self.insert_suffix_recursive(word)?,
+            CounterexampleStrategy::BinarySearch => {
+                // Слова доступа определены только для состояний ДКА
+                let dfa = nfa.determinize();
+                let dfa = dfa.as_any().downcast_ref::<AutomataImpl>().unwrap();
+                let access_words = dfa.get_access_words(self.mat.get_alphabet());
+                let is_inserted = match self.find_distinguishing_suffix(word, &access_words)? {
+                    Some(suffix) => self.insert_suffix(&suffix)?,
+                    None => false,
+                };
+                is_inserted || self.insert_suffix_recursive(word)?
+            }
+        };
+
+        if !is_changed && !self.insert_prefix_recursive(word)? {
+            return Err(MatError::NoProgress(format!(
+                "counterexample '{}' does not change the table",
+                self.mat.get_alphabet().format_word(word)
+            )));
+        }
+
+        Ok(())
+    }
+
+    // Ищется позиция i, в которой меняется ответ на u_i w[i..], где u_i -- кратчайшее
+    // слово, ведущее в то же состояние гипотезы, что и w[..i]. Если ответы на концах
+    // совпадают, гипотеза не согласована с таблицей и суффикс не ищется
+    fn find_distinguishing_suffix(
+        &self,
+        word: &[Symbol],
+        access_words: &AccessWords,
+    ) -> Result<Option<Word>, MatError> {
+        let check = |i: usize| {
+            let access_word = access_words.get(&word[..i]);
+            self.mat
+                .check_membership(&[access_word.as_slice(), &word[i..]].concat())
+        };
+
+        let mut low = 0;
+        let mut high = word.len();
+        let low_answer = check(low)?;
+        if check(high)? == low_answer {
+            return Ok(None);
+        }
+
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if check(middle)? == low_answer {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        Ok(Some(word[high..].to_vec()))
+    }
+
+    // Возвращают true, если в таблицу добавлен хотя бы один новый префикс или суффикс
+    fn insert_prefix_recursive(&mut self, prefix: &[Symbol]) -> Result<bool, MatError> {
+        let mut is_inserted = false;
         for i in 1..=prefix.len() {
             let word = &prefix[0..i];
-            self.insert_prefix(word);
+            is_inserted |= self.insert_prefix(word)?;
         }
+
+        Ok(is_inserted)
     }
 
-    fn insert_prefix(&mut self, prefix: &str) {
-        self.main_table.insert_prefix(prefix);
-        self.extended_table.insert_prefix(prefix);
+    fn insert_suffix_recursive(&mut self, suffix: &[Symbol]) -> Result<bool, MatError> {
+        let mut is_inserted = false;
+        for i in (0..suffix.len()).rev() {
+            let word = &suffix[i..];
+            is_inserted |= self.insert_suffix(word)?;
+        }
+
+        Ok(is_inserted)
     }
 
-    fn insert_suffix(&mut self, suffix: &str) {
-        self.main_table.insert_suffix(suffix);
-        self.extended_table.insert_suffix(suffix);
+    // Наблюдатели уведомляются только о префиксах и суффиксах, которых ещё не было в таблице
+    fn insert_prefix(&mut self, prefix: &[Symbol]) -> Result<bool, MatError> {
+        let is_new = !self.main_table.prefixes.contains(prefix);
+
+        self.main_table.insert_prefix(&self.mat, prefix)?;
+        self.extended_table.insert_prefix(&self.mat, prefix)?;
+
+        if is_new {
+            for observer in &mut self.observers {
+                observer.on_prefix_inserted(prefix, &self.main_table.basic_prefixes);
+            }
+        }
+
+        Ok(is_new)
+    }
+
+    fn insert_suffix(&mut self, suffix: &[Symbol]) -> Result<bool, MatError> {
+        let is_new = !self.main_table.suffixes.contains(suffix);
+
+        self.main_table.insert_suffix(&self.mat, suffix)?;
+        self.extended_table.insert_suffix(&self.mat, suffix)?;
+
+        if is_new {
+            for observer in &mut self.observers {
+                observer.on_suffix_inserted(suffix, &self.main_table.basic_prefixes);
+            }
+        }
+
+        Ok(is_new)
     }
 
     fn check_completeness(&self) -> CompletenessCheckResult {
@@ -113,9 +357,9 @@ impl<'a> NlImpl<'a> {
                     continue;
                 }
 
-                for letter in self.mat.get_alphabet().chars() {
-                    let new_prefix_1 = format!("{prefix_1}{letter}");
-                    let new_prefix_2 = format!("{prefix_2}{letter}");
+                for letter in self.mat.get_alphabet().symbols() {
+                    let new_prefix_1 = [prefix_1.as_slice(), &[letter]].concat();
+                    let new_prefix_2 = [prefix_2.as_slice(), &[letter]].concat();
 
                     let new_membership_suffixes_1 = self
                         .extended_table
@@ -132,7 +376,7 @@ impl<'a> NlImpl<'a> {
                         .difference(new_membership_suffixes_2)
                         .next()
                     {
-                        let distinguishing_suffix = format!("{letter}{suffix}");
+                        let distinguishing_suffix = [&[letter], suffix.as_slice()].concat();
                         return ConsistencyCheckResult::DistinguishingSuffix(distinguishing_suffix);
                     }
                 }
@@ -154,12 +398,12 @@ impl<'a> NlImpl<'a> {
         );
         for prefix in &epsilon_absorbed_prefixes {
             let index = prefix_to_index.get(prefix).unwrap();
-            automata.transitions[START][*index].insert(EPSILON.to_owned());
+            automata.add_epsilon_transition(START, *index);
         }
 
         for (prefix, index) in &prefix_to_index {
-            for letter in self.mat.get_alphabet().chars() {
-                let extension = format!("{prefix}{letter}");
+            for letter in self.mat.get_alphabet().symbols() {
+                let extension = [prefix.as_slice(), &[letter]].concat();
                 let extension_absorbed_prefixes = self.main_table.get_absorbed_basic_prefixes(
                     self.extended_table
                         .prefix_to_membership_suffixes
@@ -168,7 +412,7 @@ impl<'a> NlImpl<'a> {
                 );
                 for absorbed_prefix in &extension_absorbed_prefixes {
                     let absorbed_prefix_index = prefix_to_index.get(absorbed_prefix).unwrap();
-                    automata.transitions[*index][*absorbed_prefix_index].insert(letter.to_string());
+                    automata.add_transition(*index, letter, *absorbed_prefix_index);
                 }
             }
         }
@@ -187,7 +431,29 @@ impl<'a> NlImpl<'a> {
         Box::new(automata)
     }
 
-    fn enumerate_basic_prefixes(&self) -> HashMap<String, usize> {
+    // Базисные префиксы состояний NFA и их строки по всем суффиксам основной таблицы
+    fn get_hypothesis_states(&self) -> BTreeMap<usize, HypothesisState> {
+        let mut states = BTreeMap::new();
+
+        for (prefix, index) in self.enumerate_basic_prefixes() {
+            let membership_suffixes = self
+                .main_table
+                .prefix_to_membership_suffixes
+                .get(&prefix)
+                .unwrap();
+            let row = self
+                .main_table
+                .suffixes
+                .iter()
+                .map(|suffix| (suffix.to_owned(), membership_suffixes.contains(suffix)))
+                .collect();
+            states.insert(index, HypothesisState { prefix, row });
+        }
+
+        states
+    }
+
+    fn enumerate_basic_prefixes(&self) -> HashMap<Word, usize> {
         let mut prefix_to_index = HashMap::new();
 
         // Индекс 0 зарезервирован для стартового состояния
@@ -198,3 +464,65 @@ impl<'a> NlImpl<'a> {
         prefix_to_index
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::alphabet::Alphabet;
+    use crate::automata::{Automata, AutomataImpl};
+    use crate::mat::{automata_mat::AutomataMat, MatError};
+    use crate::nl::{CounterexampleStrategy, Nl, NlImpl};
+
+    fn get_random_nfa(rng: &mut StdRng, alphabet: &Alphabet) -> AutomataImpl {
+        let size = rng.gen_range(1..=5);
+        let mut nfa = AutomataImpl::new(size);
+        for from in 0..size {
+            for letter in alphabet.symbols() {
+                for to in 0..size {
+                    if rng.gen_bool(0.3) {
+                        nfa.add_transition(from, letter, to);
+                    }
+                }
+            }
+            nfa.finite_states[from] = rng.gen_bool(0.4);
+        }
+
+        nfa
+    }
+
+    // Стратегии по суффиксам всегда доходят до эквивалентной гипотезы, добавление
+    // префиксов может остановиться на контрпримере, который не меняет таблицу
+    #[test]
+    fn counterexample_strategies_learn_random_targets() {
+        let alphabet = Alphabet::from_chars("ab");
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            let target = get_random_nfa(&mut rng, &alphabet);
+            let mat = AutomataMat {
+                automata: &target,
+                alphabet: alphabet.clone(),
+            };
+
+            for strategy in [
+                CounterexampleStrategy::AllPrefixes,
+                CounterexampleStrategy::AllSuffixes,
+                CounterexampleStrategy::BinarySearch,
+            ] {
+                let result = NlImpl::new(&mat)
+                    .with_counterexample_strategy(strategy)
+                    .get_hypothesis();
+
+                match result {
+                    Ok((hypothesis, _)) => {
+                        assert_eq!(target.equivalent(hypothesis.automata.as_ref()), None)
+                    }
+                    Err(MatError::NoProgress(_))
+                        if strategy == CounterexampleStrategy::AllPrefixes => {}
+                    Err(error) => panic!("{strategy:?}: {error}"),
+                }
+            }
+        }
+    }
+}
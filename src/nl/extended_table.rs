@@ -1,65 +1,74 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::config::EPSILON;
-use crate::mat::Mat;
+use crate::alphabet::{Symbol, Word};
+use crate::mat::{Mat, MatError};
 
-pub struct ExtendedTable<'a> {
-    mat: &'a dyn Mat,
-    pub prefixes: HashSet<String>,
-    pub suffixes: HashSet<String>,
-    pub prefix_to_membership_suffixes: HashMap<String, HashSet<String>>,
+use super::query_table;
+
+pub struct ExtendedTable {
+    pub prefixes: HashSet<Word>,
+    pub suffixes: HashSet<Word>,
+    pub prefix_to_membership_suffixes: HashMap<Word, HashSet<Word>>,
 }
 
-impl<'a> ExtendedTable<'a> {
-    pub fn new(mat: &'a dyn Mat) -> Self {
-        let mut table = Self {
-            mat,
+impl ExtendedTable {
+    pub fn new() -> Self {
+        Self {
             prefixes: HashSet::new(),
             suffixes: HashSet::new(),
             prefix_to_membership_suffixes: HashMap::new(),
-        };
-
-        table.insert_prefix(EPSILON);
-        table.insert_suffix(EPSILON);
-
-        table
+        }
     }
 
-    pub fn insert_prefix(&mut self, prefix: &str) {
-        for letter in self.mat.get_alphabet().chars() {
-            let new_prefix = format!("{prefix}{letter}");
-            self.insert_prefix_impl(&new_prefix);
+    pub fn insert_prefix(&mut self, mat: &dyn Mat, prefix: &[Symbol]) -> Result<(), MatError> {
+        for letter in mat.get_alphabet().symbols() {
+            let new_prefix = [prefix, &[letter]].concat();
+            self.insert_prefix_impl(mat, &new_prefix)?;
         }
+
+        Ok(())
     }
 
-    fn insert_prefix_impl(&mut self, prefix: &str) {
+    fn insert_prefix_impl(&mut self, mat: &dyn Mat, prefix: &[Symbol]) -> Result<(), MatError> {
         if self.prefixes.contains(prefix) {
-            return;
+            return Ok(());
         }
-        self.prefixes.insert(prefix.to_owned());
 
-        let mut membership_suffixes = HashSet::new();
-        for suffix in &self.suffixes {
-            let word = format!("{prefix}{suffix}");
-            if self.mat.check_membership(&word) {
-                membership_suffixes.insert(suffix.to_owned());
-            }
-        }
+        let suffixes: Vec<Word> = self.suffixes.iter().cloned().collect();
+        let answers = query_table(mat, &[prefix.to_owned()], &suffixes)?;
+
+        let membership_suffixes: HashSet<Word> = suffixes
+            .into_iter()
+            .zip(answers)
+            .filter(|(_, is_member)| *is_member)
+            .map(|(suffix, _)| suffix)
+            .collect();
+
+        self.prefixes.insert(prefix.to_owned());
         self.prefix_to_membership_suffixes
             .insert(prefix.to_owned(), membership_suffixes);
+
+        Ok(())
     }
 
-    pub fn insert_suffix(&mut self, suffix: &str) {
+    pub fn insert_suffix(&mut self, mat: &dyn Mat, suffix: &[Symbol]) -> Result<(), MatError> {
         if self.suffixes.contains(suffix) {
-            return;
+            return Ok(());
         }
-        self.suffixes.insert(suffix.to_owned());
 
-        for (prefix, membership_suffixes) in &mut self.prefix_to_membership_suffixes {
-            let word = format!("{prefix}{suffix}");
-            if self.mat.check_membership(&word) {
-                membership_suffixes.insert(suffix.to_owned());
+        let prefixes: Vec<Word> = self.prefix_to_membership_suffixes.keys().cloned().collect();
+        let answers = query_table(mat, &prefixes, &[suffix.to_owned()])?;
+
+        self.suffixes.insert(suffix.to_owned());
+        for (prefix, is_member) in prefixes.iter().zip(answers) {
+            if is_member {
+                self.prefix_to_membership_suffixes
+                    .get_mut(prefix)
+                    .unwrap()
+                    .insert(suffix.to_owned());
             }
         }
+
+        Ok(())
     }
 }
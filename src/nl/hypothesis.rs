@@ -0,0 +1,39 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::alphabet::{Alphabet, Word};
+use crate::automata::Automata;
+
+// Гипотеза обучения: RFSA у NL*, ДКА у L* и Kearns–Vazirani.
+// Стартовое состояние NFA из NL* не соответствует базисному префиксу и в states не входит
+pub struct Hypothesis {
+    pub automata: Box<dyn Automata>,
+    pub states: BTreeMap<usize, HypothesisState>,
+}
+
+// row -- ответы на prefix·suffix для суффиксов таблицы или различающего дерева
+#[derive(Clone, Debug, PartialEq)]
+pub struct HypothesisState {
+    pub prefix: Word,
+    pub row: BTreeMap<Word, bool>,
+}
+
+impl Hypothesis {
+    pub fn determinize(&self) -> Box<dyn Automata> {
+        self.automata.determinize()
+    }
+
+    // Подписи состояний базисными префиксами для to_dot
+    pub fn get_state_annotations(&self, alphabet: &Alphabet) -> HashMap<usize, String> {
+        self.states
+            .iter()
+            .map(|(state, info)| {
+                let annotation = if info.prefix.is_empty() {
+                    "ε".to_owned()
+                } else {
+                    alphabet.format_word(&info.prefix)
+                };
+                (*state, annotation)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,257 @@
+// Алгоритм Kearns–Vazirani: состояния гипотезы различаются деревом,
+// во внутренних узлах которого -- различающие суффиксы, в листьях -- состояния.
+// Контрпример разбирается двоичным поиском (Rivest–Schapire), так что на каждое
+// новое состояние приходится O(log |w|) запросов вместо строки таблицы
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+
+use crate::alphabet::{Symbol, Word};
+use crate::automata::{Automata, AutomataImpl, START};
+use crate::config::EPSILON;
+use crate::mat::{counting_mat::CountingMat, EquivalenceCheckResult, Mat, MatError};
+use crate::nl::hypothesis::{Hypothesis, HypothesisState};
+use crate::nl::report::{LearningReport, TableSizes};
+
+use super::Nl;
+
+// Потомки внутреннего узла -- для ответов false и true на запрос prefix·suffix
+enum Node {
+    Leaf(usize),
+    Inner { suffix: Word, children: [usize; 2] },
+}
+
+pub struct KearnsVaziraniImpl<'a> {
+    mat: CountingMat<'a>,
+    nodes: Vec<Node>,
+    access_words: Vec<Word>,
+    finite_states: Vec<bool>,
+    // Переходы гипотезы по индексам символов алфавита
+    transitions: Vec<Vec<usize>>,
+    state_to_leaf: Vec<usize>,
+    // Ответы на уже заданные запросы: при просеивании и разборе контрпримеров
+    // одни и те же слова спрашиваются многократно
+    answers: HashMap<Word, bool>,
+    report: LearningReport,
+}
+
+impl<'a> Nl for KearnsVaziraniImpl<'a> {
+    fn get_hypothesis(&mut self) -> Result<(Hypothesis, LearningReport), MatError> {
+        let start = Instant::now();
+
+        let dfa = loop {
+            let timer = Instant::now();
+            let dfa = self.build_dfa();
+            self.report.time.hypothesis += timer.elapsed();
+
+            let timer = Instant::now();
+            let result = self.mat.check_equivalence(dfa.as_ref())?;
+            self.report.time.equivalence += timer.elapsed();
+
+            if let EquivalenceCheckResult::Counterexample(word, _) = result {
+                let timer = Instant::now();
+                self.process_counterexample(&word)?;
+                self.report.counterexamples += 1;
+                self.report.time.counterexamples += timer.elapsed();
+                continue;
+            }
+
+            break dfa;
+        };
+
+        let hypothesis = Hypothesis {
+            automata: dfa,
+            states: self.get_hypothesis_states(),
+        };
+
+        self.report.time.total += start.elapsed();
+        Ok((hypothesis, self.get_report()))
+    }
+}
+
+impl<'a> KearnsVaziraniImpl<'a> {
+    // Начальная гипотеза -- одно состояние с петлями по всем буквам
+    pub fn new(mat: &'a dyn Mat) -> Result<Self, MatError> {
+        let alphabet_size = mat.get_alphabet().len();
+
+        let mut kv = Self {
+            mat: CountingMat::new(mat),
+            nodes: vec![Node::Leaf(START)],
+            access_words: vec![Word::new()],
+            finite_states: vec![false],
+            transitions: vec![vec![START; alphabet_size]],
+            state_to_leaf: vec![0],
+            answers: HashMap::new(),
+            report: LearningReport::default(),
+        };
+        kv.finite_states[START] = kv.check_membership(EPSILON)?;
+
+        Ok(kv)
+    }
+
+    pub fn get_report(&self) -> LearningReport {
+        let states = self.access_words.len();
+
+        LearningReport {
+            queries: self.mat.get_statistics(),
+            table: TableSizes {
+                prefixes: states,
+                basic_prefixes: states,
+                suffixes: self.nodes.len() - states,
+                extended_prefixes: states * self.mat.get_alphabet().len(),
+            },
+            ..self.report.clone()
+        }
+    }
+
+    // Один контрпример может дать несколько новых состояний: он разбирается,
+    // пока гипотеза ошибается на нём
+    fn process_counterexample(&mut self, word: &[Symbol]) -> Result<(), MatError> {
+        let is_member = self.check_membership(word)?;
+
+        while self.finite_states[self.get_state(word)] != is_member {
+            self.split_state(word, is_member)?;
+        }
+
+        Ok(())
+    }
+
+    // Ответ на u_i w[i..], где u_i -- слово доступа состояния гипотезы после w[..i],
+    // при i = 0 совпадает с ответом на w, а при i = |w| -- с ответом гипотезы.
+    // Двоичным поиском находится i, в котором ответ меняется; тогда слово u_i w[i]
+    // и слово доступа состояния после w[..i + 1] различает суффикс w[i + 1..]
+    fn split_state(&mut self, word: &[Symbol], is_member: bool) -> Result<(), MatError> {
+        let mut low = 0;
+        let mut high = word.len();
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            let access_word = &self.access_words[self.get_state(&word[..middle])];
+            let query = [access_word.as_slice(), &word[middle..]].concat();
+            if self.check_membership(&query)? == is_member {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        let source_state = self.get_state(&word[..low]);
+        let new_access_word = [self.access_words[source_state].as_slice(), &[word[low]]].concat();
+        let old_state = self.transitions[source_state][word[low]];
+        let suffix = word[low + 1..].to_vec();
+
+        self.add_state(old_state, new_access_word, suffix, !is_member)
+    }
+
+    // Лист старого состояния заменяется узлом с новым суффиксом; ответ для старого
+    // состояния уже известен из разбора контрпримера
+    fn add_state(
+        &mut self,
+        old_state: usize,
+        access_word: Word,
+        suffix: Word,
+        old_answer: bool,
+    ) -> Result<(), MatError> {
+        let new_state = self.access_words.len();
+        let is_finite = self.check_membership(&access_word)?;
+
+        let node = self.state_to_leaf[old_state];
+        let old_leaf = self.push_node(Node::Leaf(old_state));
+        let new_leaf = self.push_node(Node::Leaf(new_state));
+        let mut children = [new_leaf, new_leaf];
+        children[old_answer as usize] = old_leaf;
+        self.nodes[node] = Node::Inner { suffix, children };
+
+        self.state_to_leaf[old_state] = old_leaf;
+        self.state_to_leaf.push(new_leaf);
+        self.access_words.push(access_word);
+        self.finite_states.push(is_finite);
+
+        // Переходы в старое состояние уточняются одним запросом к новому узлу
+        for state in 0..new_state {
+            for letter in self.mat.get_alphabet().symbols() {
+                if self.transitions[state][letter] == old_state {
+                    let word = [self.access_words[state].as_slice(), &[letter]].concat();
+                    self.transitions[state][letter] = self.sift(node, &word)?;
+                }
+            }
+        }
+
+        let mut new_transitions = Vec::new();
+        for letter in self.mat.get_alphabet().symbols() {
+            let word = [self.access_words[new_state].as_slice(), &[letter]].concat();
+            new_transitions.push(self.sift(0, &word)?);
+        }
+        self.transitions.push(new_transitions);
+
+        Ok(())
+    }
+
+    // Строка состояния -- ответы на суффиксы по пути от корня до его листа
+    fn get_hypothesis_states(&self) -> BTreeMap<usize, HypothesisState> {
+        let mut states = BTreeMap::new();
+
+        let mut nodes_to_visit = vec![(0, BTreeMap::new())];
+        while let Some((node, row)) = nodes_to_visit.pop() {
+            match &self.nodes[node] {
+                Node::Leaf(state) => {
+                    let prefix = self.access_words[*state].clone();
+                    states.insert(*state, HypothesisState { prefix, row });
+                }
+                Node::Inner { suffix, children } => {
+                    for (is_member, child) in [false, true].into_iter().zip(children) {
+                        let mut child_row = row.clone();
+                        child_row.insert(suffix.clone(), is_member);
+                        nodes_to_visit.push((*child, child_row));
+                    }
+                }
+            }
+        }
+
+        states
+    }
+
+    fn push_node(&mut self, node: Node) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    // Спуск по дереву от узла node до листа
+    fn sift(&mut self, mut node: usize, word: &[Symbol]) -> Result<usize, MatError> {
+        loop {
+            let (query, children) = match &self.nodes[node] {
+                Node::Leaf(state) => return Ok(*state),
+                Node::Inner { suffix, children } => ([word, suffix.as_slice()].concat(), *children),
+            };
+            node = children[self.check_membership(&query)? as usize];
+        }
+    }
+
+    fn check_membership(&mut self, word: &[Symbol]) -> Result<bool, MatError> {
+        if let Some(is_member) = self.answers.get(word) {
+            return Ok(*is_member);
+        }
+
+        let is_member = self.mat.check_membership(word)?;
+        self.answers.insert(word.to_vec(), is_member);
+
+        Ok(is_member)
+    }
+
+    fn get_state(&self, word: &[Symbol]) -> usize {
+        word.iter()
+            .fold(START, |state, letter| self.transitions[state][*letter])
+    }
+
+    fn build_dfa(&self) -> Box<dyn Automata> {
+        let mut automata = AutomataImpl::new(self.access_words.len());
+
+        for (state, transitions) in self.transitions.iter().enumerate() {
+            automata.finite_states[state] = self.finite_states[state];
+            for (letter, next_state) in transitions.iter().enumerate() {
+                automata.add_transition(state, letter, *next_state);
+            }
+        }
+
+        Box::new(automata)
+    }
+}
@@ -0,0 +1,278 @@
+// Алгоритм L* (Angluin): таблица наблюдений с префиксами S, суффиксами E
+// и строками для S и S·Σ, гипотеза -- ДКА, состояния которого -- различные строки S
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Instant;
+
+use crate::alphabet::{Symbol, Word};
+use crate::automata::{Automata, AutomataImpl};
+use crate::config::EPSILON;
+use crate::mat::{counting_mat::CountingMat, EquivalenceCheckResult, Mat, MatError};
+use crate::nl::hypothesis::{Hypothesis, HypothesisState};
+use crate::nl::report::{LearningReport, TableSizes};
+
+use super::{query_table, CompletenessCheckResult, ConsistencyCheckResult, Nl};
+
+pub struct LStarImpl<'a> {
+    mat: CountingMat<'a>,
+    // Префиксы и суффиксы хранятся в порядке добавления, чтобы гипотеза не зависела
+    // от порядка обхода хеш-таблиц
+    prefixes: Vec<Word>,
+    prefix_set: HashSet<Word>,
+    suffixes: Vec<Word>,
+    // Ответы для слов prefix·suffix в порядке suffixes, prefix из S и S·Σ
+    rows: HashMap<Word, Vec<bool>>,
+    report: LearningReport,
+}
+
+impl<'a> Nl for LStarImpl<'a> {
+    fn get_hypothesis(&mut self) -> Result<(Hypothesis, LearningReport), MatError> {
+        let start = Instant::now();
+
+        let dfa = loop {
+            let timer = Instant::now();
+            let is_fixed = self.fix_closedness()?;
+            self.report.time.completeness += timer.elapsed();
+            if is_fixed {
+                continue;
+            }
+
+            let timer = Instant::now();
+            let is_fixed = self.fix_consistency()?;
+            self.report.time.consistency += timer.elapsed();
+            if is_fixed {
+                continue;
+            }
+
+            let timer = Instant::now();
+            let dfa = self.build_dfa();
+            self.report.time.hypothesis += timer.elapsed();
+
+            let timer = Instant::now();
+            let result = self.mat.check_equivalence(dfa.as_ref())?;
+            self.report.time.equivalence += timer.elapsed();
+
+            if let EquivalenceCheckResult::Counterexample(word, _) = result {
+                let timer = Instant::now();
+                let mut is_inserted = false;
+                for i in 1..=word.len() {
+                    is_inserted |= self.insert_prefix(&word[0..i])?;
+                }
+                if !is_inserted {
+                    return Err(MatError::NoProgress(format!(
+                        "counterexample '{}' does not change the table",
+                        self.mat.get_alphabet().format_word(&word)
+                    )));
+                }
+                self.report.counterexamples += 1;
+                self.report.time.counterexamples += timer.elapsed();
+                continue;
+            }
+
+            break dfa;
+        };
+
+        let hypothesis = Hypothesis {
+            automata: dfa,
+            states: self.get_hypothesis_states(),
+        };
+
+        self.report.time.total += start.elapsed();
+        Ok((hypothesis, self.get_report()))
+    }
+}
+
+impl<'a> LStarImpl<'a> {
+    pub fn new(mat: &'a dyn Mat) -> Result<Self, MatError> {
+        let mut lstar = Self {
+            mat: CountingMat::new(mat),
+            prefixes: Vec::new(),
+            prefix_set: HashSet::new(),
+            suffixes: Vec::new(),
+            rows: HashMap::new(),
+            report: LearningReport::default(),
+        };
+
+        lstar.insert_suffix(EPSILON)?;
+        lstar.insert_prefix(EPSILON)?;
+
+        Ok(lstar)
+    }
+
+    pub fn get_report(&self) -> LearningReport {
+        LearningReport {
+            queries: self.mat.get_statistics(),
+            table: TableSizes {
+                prefixes: self.prefixes.len(),
+                basic_prefixes: self.get_state_rows().len(),
+                suffixes: self.suffixes.len(),
+                extended_prefixes: self.prefixes.len() * self.mat.get_alphabet().len(),
+            },
+            ..self.report.clone()
+        }
+    }
+
+    fn fix_closedness(&mut self) -> Result<bool, MatError> {
+        let CompletenessCheckResult::UncoveredPrefix(prefix) = self.check_closedness() else {
+            return Ok(false);
+        };
+
+        self.insert_prefix(&prefix)?;
+        self.report.completeness_fixes += 1;
+        Ok(true)
+    }
+
+    fn fix_consistency(&mut self) -> Result<bool, MatError> {
+        let ConsistencyCheckResult::DistinguishingSuffix(suffix) = self.check_consistency() else {
+            return Ok(false);
+        };
+
+        self.insert_suffix(&suffix)?;
+        self.report.consistency_fixes += 1;
+        Ok(true)
+    }
+
+    fn check_closedness(&self) -> CompletenessCheckResult {
+        let state_rows = self.get_state_rows();
+
+        for prefix in &self.prefixes {
+            for letter in self.mat.get_alphabet().symbols() {
+                let extension = [prefix.as_slice(), &[letter]].concat();
+                if !state_rows.contains_key(&self.rows[&extension]) {
+                    return CompletenessCheckResult::UncoveredPrefix(extension);
+                }
+            }
+        }
+
+        CompletenessCheckResult::Ok
+    }
+
+    fn check_consistency(&self) -> ConsistencyCheckResult {
+        for (i, prefix_1) in self.prefixes.iter().enumerate() {
+            for prefix_2 in &self.prefixes[i + 1..] {
+                if self.rows[prefix_1] != self.rows[prefix_2] {
+                    continue;
+                }
+
+                for letter in self.mat.get_alphabet().symbols() {
+                    let row_1 = &self.rows[&[prefix_1.as_slice(), &[letter]].concat()];
+                    let row_2 = &self.rows[&[prefix_2.as_slice(), &[letter]].concat()];
+
+                    if let Some(index) = (0..row_1.len()).find(|j| row_1[*j] != row_2[*j]) {
+                        let distinguishing_suffix =
+                            [&[letter], self.suffixes[index].as_slice()].concat();
+                        return ConsistencyCheckResult::DistinguishingSuffix(distinguishing_suffix);
+                    }
+                }
+            }
+        }
+
+        ConsistencyCheckResult::Ok
+    }
+
+    fn build_dfa(&self) -> Box<dyn Automata> {
+        let state_rows = self.get_state_rows();
+        let mut automata = AutomataImpl::new(state_rows.len());
+
+        for prefix in &self.prefixes {
+            let state = state_rows[&self.rows[prefix]];
+            // Суффикс ε добавляется первым
+            automata.finite_states[state] = self.rows[prefix][0];
+
+            for letter in self.mat.get_alphabet().symbols() {
+                let extension = [prefix.as_slice(), &[letter]].concat();
+                automata.add_transition(state, letter, state_rows[&self.rows[&extension]]);
+            }
+        }
+
+        Box::new(automata)
+    }
+
+    // Состояние подписывается первым префиксом из S с его строкой
+    fn get_hypothesis_states(&self) -> BTreeMap<usize, HypothesisState> {
+        let mut states = BTreeMap::new();
+
+        for (row, state) in self.get_state_rows() {
+            let prefix = self
+                .prefixes
+                .iter()
+                .find(|prefix| &self.rows[*prefix] == row)
+                .unwrap();
+            let row = self
+                .suffixes
+                .iter()
+                .cloned()
+                .zip(row.iter().copied())
+                .collect();
+            states.insert(
+                state,
+                HypothesisState {
+                    prefix: prefix.to_owned(),
+                    row,
+                },
+            );
+        }
+
+        states
+    }
+
+    // Различные строки префиксов из S в порядке первого появления, строка ε -- стартовая
+    fn get_state_rows(&self) -> HashMap<&Vec<bool>, usize> {
+        let mut state_rows = HashMap::new();
+
+        for prefix in &self.prefixes {
+            let next_state = state_rows.len();
+            state_rows.entry(&self.rows[prefix]).or_insert(next_state);
+        }
+
+        state_rows
+    }
+
+    fn insert_prefix(&mut self, prefix: &[Symbol]) -> Result<bool, MatError> {
+        if self.prefix_set.contains(prefix) {
+            return Ok(false);
+        }
+
+        let mut new_rows: Vec<Word> = vec![prefix.to_owned()];
+        new_rows.extend(
+            self.mat
+                .get_alphabet()
+                .symbols()
+                .map(|letter| [prefix, &[letter]].concat()),
+        );
+        new_rows.retain(|row| !self.rows.contains_key(row));
+
+        let answers = query_table(&self.mat, &new_rows, &self.suffixes)?;
+
+        for (row, answers) in new_rows
+            .into_iter()
+            .zip(answers.chunks(self.suffixes.len()))
+        {
+            self.rows.insert(row, answers.to_vec());
+        }
+        self.prefixes.push(prefix.to_owned());
+        self.prefix_set.insert(prefix.to_owned());
+
+        Ok(true)
+    }
+
+    fn insert_suffix(&mut self, suffix: &[Symbol]) -> Result<(), MatError> {
+        if self
+            .suffixes
+            .iter()
+            .any(|known_suffix| known_suffix == suffix)
+        {
+            return Ok(());
+        }
+
+        let rows: Vec<Word> = self.rows.keys().cloned().collect();
+        let answers = query_table(&self.mat, &rows, &[suffix.to_owned()])?;
+
+        self.suffixes.push(suffix.to_owned());
+        for (row, is_member) in rows.iter().zip(answers) {
+            self.rows.get_mut(row).unwrap().push(is_member);
+        }
+
+        Ok(())
+    }
+}
@@ -1,15 +1,16 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::config::EPSILON;
-use crate::mat::Mat;
-
-pub struct MainTable<'a> {
-    mat: &'a dyn Mat,
-    pub prefixes: HashSet<String>,
-    pub basic_prefixes: HashSet<String>,
-    pub suffixes: HashSet<String>,
-    pub prefix_to_membership_suffixes: HashMap<String, HashSet<String>>,
-    pub suffix_to_membership_prefixes: HashMap<String, HashSet<String>>,
+use crate::alphabet::{Symbol, Word};
+use crate::mat::{Mat, MatError};
+
+use super::query_table;
+
+pub struct MainTable {
+    pub prefixes: HashSet<Word>,
+    pub basic_prefixes: HashSet<Word>,
+    pub suffixes: HashSet<Word>,
+    pub prefix_to_membership_suffixes: HashMap<Word, HashSet<Word>>,
+    pub suffix_to_membership_prefixes: HashMap<Word, HashSet<Word>>,
 }
 
 pub enum CoverageMode {
@@ -19,37 +20,38 @@ pub enum CoverageMode {
 
 enum EquivalentBasicPrefixSearchResult {
     NotFound,
-    Found(String),
+    Found(Word),
 }
 
-impl<'a> MainTable<'a> {
-    pub fn new(mat: &'a dyn Mat) -> Self {
-        let mut table = Self {
-            mat,
+impl MainTable {
+    pub fn new() -> Self {
+        Self {
             prefixes: HashSet::new(),
             basic_prefixes: HashSet::new(),
             suffixes: HashSet::new(),
             prefix_to_membership_suffixes: HashMap::new(),
             suffix_to_membership_prefixes: HashMap::new(),
-        };
-
-        table.insert_prefix(EPSILON);
-        table.insert_suffix(EPSILON);
-
-        table
+        }
     }
 
-    pub fn insert_prefix(&mut self, prefix: &str) {
+    pub fn insert_prefix(&mut self, mat: &dyn Mat, prefix: &[Symbol]) -> Result<(), MatError> {
         if self.prefixes.contains(prefix) {
-            return;
+            return Ok(());
         }
+
+        let suffixes: Vec<Word> = self.suffix_to_membership_prefixes.keys().cloned().collect();
+        let answers = query_table(mat, &[prefix.to_owned()], &suffixes)?;
+
         self.prefixes.insert(prefix.to_owned());
 
         let mut membership_suffixes = HashSet::new();
-        for (suffix, membership_prefixes) in &mut self.suffix_to_membership_prefixes {
-            if self.mat.check_membership(&format!("{prefix}{suffix}")) {
-                membership_suffixes.insert(suffix.to_owned());
-                membership_prefixes.insert(prefix.to_owned());
+        for (suffix, is_member) in suffixes.into_iter().zip(answers) {
+            if is_member {
+                self.suffix_to_membership_prefixes
+                    .get_mut(&suffix)
+                    .unwrap()
+                    .insert(prefix.to_owned());
+                membership_suffixes.insert(suffix);
             }
         }
         self.prefix_to_membership_suffixes
@@ -67,11 +69,13 @@ impl<'a> MainTable<'a> {
             self.basic_prefixes.insert(prefix.to_owned());
             self.cleanup_basic_prefixes();
         }
+
+        Ok(())
     }
 
     fn find_equivalent_basic_prefix(
         &self,
-        desired_membership_suffixes: &HashSet<String>,
+        desired_membership_suffixes: &HashSet<Word>,
     ) -> EquivalentBasicPrefixSearchResult {
         for basic_prefix in &self.basic_prefixes {
             let membership_suffixes = self
@@ -86,15 +90,15 @@ impl<'a> MainTable<'a> {
         EquivalentBasicPrefixSearchResult::NotFound
     }
 
-    fn is_shorter(&self, first_prefix: &str, second_prefix: &str) -> bool {
+    fn is_shorter(&self, first_prefix: &[Symbol], second_prefix: &[Symbol]) -> bool {
         // TODO? лексикографическое сравнение при равенстве длин
         first_prefix.len() < second_prefix.len()
     }
 
     pub fn is_covered(
         &self,
-        prefix: &str,
-        membership_suffixes: &HashSet<String>,
+        prefix: &[Symbol],
+        membership_suffixes: &HashSet<Word>,
         mode: CoverageMode,
     ) -> bool {
         if membership_suffixes.is_empty() && matches!(mode, CoverageMode::Exclusive) {
@@ -136,7 +140,7 @@ impl<'a> MainTable<'a> {
     }
 
     fn cleanup_basic_prefixes(&mut self) {
-        let mut not_basic_prefixes_anymore = Vec::<String>::new();
+        let mut not_basic_prefixes_anymore = Vec::<Word>::new();
 
         for prefix in &self.basic_prefixes {
             let membership_suffixes = self.prefix_to_membership_suffixes.get(prefix).unwrap();
@@ -150,24 +154,32 @@ impl<'a> MainTable<'a> {
         }
     }
 
-    pub fn insert_suffix(&mut self, suffix: &str) {
+    pub fn insert_suffix(&mut self, mat: &dyn Mat, suffix: &[Symbol]) -> Result<(), MatError> {
         if self.suffixes.contains(suffix) {
-            return;
+            return Ok(());
         }
+
+        let prefixes: Vec<Word> = self.prefix_to_membership_suffixes.keys().cloned().collect();
+        let answers = query_table(mat, &prefixes, &[suffix.to_owned()])?;
+
         self.suffixes.insert(suffix.to_owned());
 
         let mut membership_prefixes = HashSet::new();
-        for (prefix, membership_suffixes) in &mut self.prefix_to_membership_suffixes {
-            let word = format!("{prefix}{suffix}");
-            if self.mat.check_membership(&word) {
-                membership_prefixes.insert(prefix.to_owned());
-                membership_suffixes.insert(suffix.to_owned());
+        for (prefix, is_member) in prefixes.into_iter().zip(answers) {
+            if is_member {
+                self.prefix_to_membership_suffixes
+                    .get_mut(&prefix)
+                    .unwrap()
+                    .insert(suffix.to_owned());
+                membership_prefixes.insert(prefix);
             }
         }
         self.suffix_to_membership_prefixes
             .insert(suffix.to_owned(), membership_prefixes);
 
         self.rebuild_basic_prefixes();
+
+        Ok(())
     }
 
     fn rebuild_basic_prefixes(&mut self) {
@@ -182,8 +194,8 @@ impl<'a> MainTable<'a> {
     // NOTE: наивная реализация.
     pub fn get_absorbed_basic_prefixes(
         &self,
-        source_membership_suffixes: &HashSet<String>,
-    ) -> HashSet<String> {
+        source_membership_suffixes: &HashSet<Word>,
+    ) -> HashSet<Word> {
         let mut absorbed_prefixes = HashSet::new();
 
         for prefix in &self.basic_prefixes {
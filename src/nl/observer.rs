@@ -0,0 +1,147 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::alphabet::{Alphabet, Symbol, Word};
+use crate::automata::Automata;
+use crate::mat::CounterexampleKind;
+
+// Вызывается NlImpl на каждом шаге обучения, по умолчанию ничего не делает
+pub trait NlObserver {
+    fn on_uncovered_prefix(&mut self, _prefix: &[Symbol]) {}
+
+    fn on_distinguishing_suffix(&mut self, _suffix: &[Symbol]) {}
+
+    fn on_prefix_inserted(&mut self, _prefix: &[Symbol], _basic_prefixes: &HashSet<Word>) {}
+
+    fn on_suffix_inserted(&mut self, _suffix: &[Symbol], _basic_prefixes: &HashSet<Word>) {}
+
+    fn on_hypothesis_built(&mut self, _nfa: &dyn Automata) {}
+
+    fn on_counterexample(&mut self, _word: &[Symbol], _kind: CounterexampleKind) {}
+}
+
+#[derive(Serialize)]
+struct EventLine {
+    step: usize,
+    #[serde(flatten)]
+    event: Event,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event {
+    UncoveredPrefix {
+        prefix: String,
+    },
+    DistinguishingSuffix {
+        suffix: String,
+    },
+    PrefixInserted {
+        prefix: String,
+        basic_prefixes: Vec<String>,
+    },
+    SuffixInserted {
+        suffix: String,
+        basic_prefixes: Vec<String>,
+    },
+    HypothesisBuilt {
+        nfa_states: usize,
+    },
+    Counterexample {
+        word: String,
+        kind: &'static str,
+    },
+}
+
+// Журнал событий по одному JSON-объекту в строке. Ошибки записи игнорируются,
+// чтобы журнал не прерывал обучение
+pub struct JsonLinesLogger<W: Write> {
+    writer: W,
+    alphabet: Alphabet,
+    step: usize,
+}
+
+impl<W: Write> JsonLinesLogger<W> {
+    pub fn new(writer: W, alphabet: Alphabet) -> Self {
+        Self {
+            writer,
+            alphabet,
+            step: 0,
+        }
+    }
+
+    fn log(&mut self, event: Event) {
+        let line = EventLine {
+            step: self.step,
+            event,
+        };
+        self.step += 1;
+
+        let _ = serde_json::to_writer(&mut self.writer, &line);
+        let _ = writeln!(self.writer);
+        let _ = self.writer.flush();
+    }
+
+    fn format_word(&self, word: &[Symbol]) -> String {
+        self.alphabet.format_word(word)
+    }
+
+    // Базисные префиксы в порядке длины, чтобы строки журнала можно было сравнивать
+    fn format_prefixes(&self, prefixes: &HashSet<Word>) -> Vec<String> {
+        let mut prefixes: Vec<&Word> = prefixes.iter().collect();
+        prefixes.sort_by(|first, second| (first.len(), first).cmp(&(second.len(), second)));
+
+        prefixes
+            .into_iter()
+            .map(|prefix| self.format_word(prefix))
+            .collect()
+    }
+}
+
+impl<W: Write> NlObserver for JsonLinesLogger<W> {
+    fn on_uncovered_prefix(&mut self, prefix: &[Symbol]) {
+        let prefix = self.format_word(prefix);
+        self.log(Event::UncoveredPrefix { prefix });
+    }
+
+    fn on_distinguishing_suffix(&mut self, suffix: &[Symbol]) {
+        let suffix = self.format_word(suffix);
+        self.log(Event::DistinguishingSuffix { suffix });
+    }
+
+    fn on_prefix_inserted(&mut self, prefix: &[Symbol], basic_prefixes: &HashSet<Word>) {
+        let prefix = self.format_word(prefix);
+        let basic_prefixes = self.format_prefixes(basic_prefixes);
+        self.log(Event::PrefixInserted {
+            prefix,
+            basic_prefixes,
+        });
+    }
+
+    fn on_suffix_inserted(&mut self, suffix: &[Symbol], basic_prefixes: &HashSet<Word>) {
+        let suffix = self.format_word(suffix);
+        let basic_prefixes = self.format_prefixes(basic_prefixes);
+        self.log(Event::SuffixInserted {
+            suffix,
+            basic_prefixes,
+        });
+    }
+
+    // Детерминизация экспоненциальна, поэтому в журнал пишется только размер NFA
+    fn on_hypothesis_built(&mut self, nfa: &dyn Automata) {
+        self.log(Event::HypothesisBuilt {
+            nfa_states: nfa.get_size(),
+        });
+    }
+
+    fn on_counterexample(&mut self, word: &[Symbol], kind: CounterexampleKind) {
+        let word = self.format_word(word);
+        let kind = match kind {
+            CounterexampleKind::Missing => "missing",
+            CounterexampleKind::Extra => "extra",
+        };
+        self.log(Event::Counterexample { word, kind });
+    }
+}
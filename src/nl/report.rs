@@ -0,0 +1,74 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::mat::counting_mat::QueryStatistics;
+
+// Сводка по одному запуску обучения
+#[derive(Clone, Debug, Default)]
+pub struct LearningReport {
+    pub queries: QueryStatistics,
+    pub completeness_fixes: usize,
+    pub consistency_fixes: usize,
+    pub counterexamples: usize,
+    pub table: TableSizes,
+    pub time: PhaseTimes,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TableSizes {
+    pub prefixes: usize,
+    pub basic_prefixes: usize,
+    pub suffixes: usize,
+    pub extended_prefixes: usize,
+}
+
+// Время проверок вместе с исправлением таблицы по их результатам
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PhaseTimes {
+    pub completeness: Duration,
+    pub consistency: Duration,
+    pub hypothesis: Duration,
+    pub equivalence: Duration,
+    pub counterexamples: Duration,
+    pub total: Duration,
+}
+
+impl fmt::Display for LearningReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let queries = &self.queries;
+        writeln!(
+            f,
+            "membership queries:  {} ({} unique, {} repeated)",
+            queries.membership_queries,
+            queries.unique_membership_queries,
+            queries.membership_queries - queries.unique_membership_queries
+        )?;
+        writeln!(f, "equivalence queries: {}", queries.equivalence_queries)?;
+        writeln!(
+            f,
+            "table fixes:         {} completeness, {} consistency, {} counterexamples",
+            self.completeness_fixes, self.consistency_fixes, self.counterexamples
+        )?;
+        writeln!(
+            f,
+            "table size:          {} prefixes ({} basic), {} suffixes, {} extended prefixes",
+            self.table.prefixes,
+            self.table.basic_prefixes,
+            self.table.suffixes,
+            self.table.extended_prefixes
+        )?;
+
+        let time = &self.time;
+        write!(
+            f,
+            "time:                {:?} total, {:?} completeness, {:?} consistency, \
+             {:?} hypothesis, {:?} equivalence, {:?} counterexamples",
+            time.total,
+            time.completeness,
+            time.consistency,
+            time.hypothesis,
+            time.equivalence,
+            time.counterexamples
+        )
+    }
+}
@@ -1,38 +1,42 @@
-use crate::{config::PUMP_TESTS, mat::Mat};
+use crate::{
+    alphabet::Word,
+    mat::{Mat, MatError},
+};
 
 pub struct ShatteredWord {
-    pub w1: String,
-    pub w2: String,
-    pub w3: String,
-    pub w4: String,
-    pub w5: String,
+    pub w1: Word,
+    pub w2: Word,
+    pub w3: Word,
+    pub w4: Word,
+    pub w5: Word,
 }
 
 pub trait Pumper {
-    fn pump_word(&self, word: &ShatteredWord) -> bool;
+    fn pump_word(&self, word: &ShatteredWord) -> Result<bool, MatError>;
 }
 
 pub struct PumperImpl<'a> {
     pub mat: &'a dyn Mat,
+    pub tests: usize,
 }
 
 impl<'a> Pumper for PumperImpl<'a> {
-    fn pump_word(&self, word: &ShatteredWord) -> bool {
-        for i in 0..PUMP_TESTS {
-            let pumped_word: String = format!(
-                "{}{}{}{}{}",
-                word.w1,
-                word.w2.repeat(i).to_string(),
-                word.w3,
-                word.w4.repeat(i).to_string(),
-                word.w5
-            );
-            let res: bool = self.mat.check_membership(&pumped_word);
+    fn pump_word(&self, word: &ShatteredWord) -> Result<bool, MatError> {
+        for i in 0..self.tests {
+            let pumped_word: Word = [
+                word.w1.as_slice(),
+                &word.w2.repeat(i),
+                &word.w3,
+                &word.w4.repeat(i),
+                &word.w5,
+            ]
+            .concat();
+            let res: bool = self.mat.check_membership(&pumped_word)?;
             if !res {
-                return false;
+                return Ok(false);
             }
         }
 
-        return true;
+        Ok(true)
     }
 }